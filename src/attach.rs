@@ -0,0 +1,112 @@
+//! Attaching the local terminal to a running container
+
+use std::io::{IsTerminal, Read};
+
+use futures_util::stream::StreamExt;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+use bollard::container::{AttachContainerOptions, AttachContainerResults, InspectContainerOptions};
+use bollard::Docker;
+
+use crate::error::CliError;
+use crate::write_log_frame;
+
+/// The sequence `docker attach` detaches on unless told otherwise.
+pub const DEFAULT_DETACH_KEYS: &str = "ctrl-p,ctrl-q";
+
+/// Holds the terminal in raw mode, so keystrokes (Ctrl-C included) go to the container
+/// unprocessed, and puts it back however the attach ends.
+struct RawMode;
+
+impl RawMode {
+    /// Only a terminal can be put into raw mode, piped stdin is forwarded as it is.
+    fn enable() -> std::io::Result<Option<RawMode>> {
+        if !std::io::stdin().is_terminal() {
+            return Ok(None);
+        }
+        crossterm::terminal::enable_raw_mode()?;
+        Ok(Some(RawMode))
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
+/// Forwards stdin to the container and its output to stdout/stderr until the container exits
+/// or `detach_keys` are typed, which the daemon watches for and answers by closing the stream.
+/// Passes a non-zero exit status on like `ps exec`.
+pub async fn attach(
+    docker: &Docker,
+    id: &str,
+    detach_keys: &str,
+    no_stdin: bool,
+) -> Result<(), CliError> {
+    let inspect = docker
+        .inspect_container(id, None::<InspectContainerOptions>)
+        .await?;
+    let tty = inspect
+        .config
+        .and_then(|config| config.tty)
+        .unwrap_or(false);
+
+    let options = AttachContainerOptions {
+        stdin: Some(!no_stdin),
+        stdout: Some(true),
+        stderr: Some(true),
+        stream: Some(true),
+        logs: Some(false),
+        detach_keys: Some(detach_keys),
+    };
+    let AttachContainerResults {
+        mut output,
+        mut input,
+    } = docker.attach_container(id, Some(options)).await?;
+
+    // Raw mode only makes sense when the container has a terminal of its own to interpret keys
+    let raw_mode = if tty { RawMode::enable()? } else { None };
+
+    if !no_stdin {
+        // A plain thread rather than tokio's stdin, whose blocking read would hold the
+        // runtime open after the container is gone until another key is pressed
+        let (sender, mut receiver) = mpsc::channel::<Vec<u8>>(16);
+        std::thread::spawn(move || {
+            let mut stdin = std::io::stdin();
+            let mut buffer = [0; 1024];
+            while let Ok(read @ 1..) = stdin.read(&mut buffer) {
+                if sender.blocking_send(buffer[..read].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        tokio::spawn(async move {
+            while let Some(chunk) = receiver.recv().await {
+                if input.write_all(&chunk).await.is_err() || input.flush().await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    while let Some(frame) = output.next().await {
+        write_log_frame(frame?)?;
+    }
+    drop(raw_mode);
+
+    let state = docker
+        .inspect_container(id, None::<InspectContainerOptions>)
+        .await?
+        .state
+        .unwrap_or_default();
+    if state.running == Some(true) {
+        eprintln!("Detached, container {id} is still running");
+        return Ok(());
+    }
+    match state.exit_code {
+        Some(0) | None => Ok(()),
+        Some(code) => Err(CliError::ExitStatus(u8::try_from(code).unwrap_or(1))),
+    }
+}
@@ -0,0 +1,200 @@
+//! Packing a build context directory into the tarball the daemon builds from
+
+use std::path::{Component, Path, PathBuf};
+
+use glob::{MatchOptions, Pattern};
+
+use crate::error::CliError;
+
+/// `*` and `?` stop at path separators, like Go's `filepath.Match` the daemon uses.
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// One line of a `.dockerignore` file.
+struct IgnoreRule {
+    pattern: Pattern,
+    /// `!pattern` re-includes paths an earlier rule excluded
+    negated: bool,
+}
+
+/// The rules of a context's `.dockerignore`, applied in order with the last match winning.
+pub struct DockerIgnore {
+    rules: Vec<IgnoreRule>,
+}
+
+impl DockerIgnore {
+    /// Reads `<context>/.dockerignore`, which is optional.
+    pub fn load(context: &Path) -> Result<DockerIgnore, CliError> {
+        let path = context.join(".dockerignore");
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut rules = Vec::new();
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(line) => (true, line.trim()),
+                None => (false, line),
+            };
+            // Patterns are always relative to the context root
+            let line = line.trim_start_matches("./").trim_start_matches('/');
+            let line = line.trim_end_matches('/');
+
+            let pattern = Pattern::new(line).map_err(|e| {
+                CliError::BuildContext(format!("invalid .dockerignore pattern {line:?}: {e}"))
+            })?;
+            rules.push(IgnoreRule { pattern, negated });
+        }
+        Ok(DockerIgnore { rules })
+    }
+
+    /// Whether `path` (relative to the context, `/`-separated) should be left out. A rule
+    /// matching a directory covers everything below it.
+    pub fn is_excluded(&self, path: &str) -> bool {
+        let mut excluded = false;
+        for IgnoreRule { pattern, negated } in &self.rules {
+            let matched = ancestors(path).any(|p| pattern.matches_with(p, MATCH_OPTIONS));
+            if matched {
+                excluded = !negated;
+            }
+        }
+        excluded
+    }
+}
+
+/// `a/b/c`, then `a/b`, then `a`.
+fn ancestors(path: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(path), |p| p.rsplit_once('/').map(|(parent, _)| parent))
+}
+
+/// Where the `-f` Dockerfile sits in the context, `/`-separated with `.` components dropped, so
+/// `./Dockerfile`, `Dockerfile` and `<context>/Dockerfile` all come out as `Dockerfile`.
+pub fn dockerfile_path(context: &Path, dockerfile: &str) -> String {
+    let path = Path::new(dockerfile);
+    let path = path.strip_prefix(context).unwrap_or(path);
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Tars up the `context` directory, skipping whatever `.dockerignore` excludes. The Dockerfile
+/// is always sent, even if it or a directory above it is ignored, since the daemon can't build
+/// without it.
+pub fn tar_context(context: &Path, dockerfile: &str) -> Result<Vec<u8>, CliError> {
+    let ignore = DockerIgnore::load(context)?;
+    let dockerfile = dockerfile_path(context, dockerfile);
+    let mut archive = tar::Builder::new(Vec::new());
+    archive.follow_symlinks(false);
+    let mut sent_dockerfile = false;
+
+    let mut pending = vec![PathBuf::new()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(context.join(&dir))? {
+            let entry = entry?;
+            let relative = dir.join(entry.file_name());
+            let name = relative.to_string_lossy().replace('\\', "/");
+
+            if ignore.is_excluded(&name) {
+                continue;
+            }
+
+            if entry.file_type()?.is_dir() {
+                archive.append_dir(&relative, entry.path())?;
+                pending.push(relative);
+            } else {
+                archive.append_path_with_name(entry.path(), &relative)?;
+                sent_dockerfile |= name == dockerfile;
+            }
+        }
+    }
+
+    // Ignored, so the walk skipped it
+    let path = context.join(&dockerfile);
+    if !sent_dockerfile && path.is_file() {
+        archive.append_path_with_name(path, &dockerfile)?;
+    }
+
+    Ok(archive.into_inner()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    /// The paths of the entries in `tarball`.
+    fn entries(tarball: &[u8]) -> Vec<String> {
+        let mut archive = tar::Archive::new(tarball);
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                entry
+                    .unwrap()
+                    .path()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dockerfile_path_drops_current_dir_components() {
+        let context = Path::new("/src/app");
+        assert_eq!(dockerfile_path(context, "Dockerfile"), "Dockerfile");
+        assert_eq!(dockerfile_path(context, "./Dockerfile"), "Dockerfile");
+        assert_eq!(
+            dockerfile_path(context, "./docker/./Dockerfile"),
+            "docker/Dockerfile"
+        );
+        assert_eq!(
+            dockerfile_path(context, "/src/app/Dockerfile"),
+            "Dockerfile"
+        );
+    }
+
+    #[test]
+    fn ignored_dockerfile_is_still_sent() {
+        let context = TempDir::new("context-ignored-dockerfile");
+        std::fs::write(context.0.join("Dockerfile"), "FROM scratch\n").unwrap();
+        std::fs::write(context.0.join("notes.txt"), "").unwrap();
+        std::fs::write(context.0.join(".dockerignore"), "Dockerfile\nnotes.txt\n").unwrap();
+
+        let tarball = tar_context(&context.0, "./Dockerfile").unwrap();
+        let entries = entries(&tarball);
+        assert!(entries.contains(&"Dockerfile".to_string()), "{entries:?}");
+        assert!(!entries.contains(&"notes.txt".to_string()), "{entries:?}");
+    }
+
+    #[test]
+    fn dockerfile_in_ignored_directory_is_still_sent() {
+        let context = TempDir::new("context-ignored-dir");
+        std::fs::create_dir(context.0.join("docker")).unwrap();
+        std::fs::write(context.0.join("docker/Dockerfile"), "FROM scratch\n").unwrap();
+        std::fs::write(context.0.join("docker/secret"), "").unwrap();
+        std::fs::write(context.0.join(".dockerignore"), "docker\n").unwrap();
+
+        let tarball = tar_context(&context.0, "docker/Dockerfile").unwrap();
+        let entries = entries(&tarball);
+        assert!(
+            entries.contains(&"docker/Dockerfile".to_string()),
+            "{entries:?}"
+        );
+        assert!(
+            !entries.contains(&"docker/secret".to_string()),
+            "{entries:?}"
+        );
+    }
+}
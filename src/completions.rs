@@ -0,0 +1,29 @@
+//! Shell completion scripts
+
+use clap::{Args, CommandFactory};
+use clap_complete::Shell;
+
+use crate::Cli;
+
+#[derive(Debug, Args)]
+#[command(after_long_help = "\
+Installing the script:
+  bash        locci completions bash > ~/.local/share/bash-completion/completions/locci
+  zsh         locci completions zsh > ~/.zfunc/_locci  (with `fpath+=~/.zfunc` before `compinit` in ~/.zshrc)
+  fish        locci completions fish > ~/.config/fish/completions/locci.fish
+  powershell  locci completions powershell >> $PROFILE
+  elvish      locci completions elvish >> ~/.config/elvish/rc.elv
+
+Open a new shell afterwards to pick the completions up.")]
+pub struct Completions {
+    /// Shell to generate the completion script for
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+// ./exe completions <shell>
+pub fn run(Completions { shell }: &Completions) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(*shell, &mut command, name, &mut std::io::stdout());
+}
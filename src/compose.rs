@@ -0,0 +1,663 @@
+//! `compose up`/`down`: the part of Docker Compose a single YAML file of services needs
+//!
+//! Each service may set `image`, `container_name`, `command`, `environment`, `ports`,
+//! `volumes`, `depends_on` and `restart`; any other service field is rejected rather than
+//! silently ignored. The services share a `<project>_default` network on which they reach each
+//! other by service name, and their containers carry Compose's own project and service labels,
+//! which is how `down` finds them again.
+//!
+//! ```yaml
+//! services:
+//!   db:
+//!     image: postgres:16
+//!     environment:
+//!       POSTGRES_PASSWORD: example
+//!     volumes: ["pgdata:/var/lib/postgresql/data"]
+//!   web:
+//!     image: ghcr.io/example/web:1.4
+//!     ports: ["8080:80"]
+//!     depends_on: [db]
+//!     restart: unless-stopped
+//! ```
+
+use clap::{Args, Subcommand};
+use std::collections::{BTreeMap, HashMap};
+use std::default::Default;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use futures_util::stream::StreamExt;
+
+use bollard::container::{
+    Config, CreateContainerOptions, InspectContainerOptions, ListContainersOptions,
+    NetworkingConfig, RemoveContainerOptions, StartContainerOptions, StopContainerOptions,
+};
+use bollard::errors::Error::DockerResponseServerError;
+use bollard::image::CreateImageOptions;
+use bollard::models::{ContainerSummary, CreateImageInfo, EndpointSettings, HostConfig};
+use bollard::network::{CreateNetworkOptions, InspectNetworkOptions};
+use bollard::Docker;
+
+use crate::error::{CliError, Failures};
+use crate::run::{exposed_ports, parse_port, port_bindings};
+use crate::{parse_restart_policy, split_image_ref};
+
+/// The labels Docker Compose puts on what it creates, used here too so either tool recognizes
+/// the other's containers.
+const PROJECT_LABEL: &str = "com.docker.compose.project";
+const SERVICE_LABEL: &str = "com.docker.compose.service";
+
+/// Looked for in this order when `--file` isn't given, like `docker compose` does.
+const DEFAULT_FILES: [&str; 4] = [
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+#[derive(Debug, Args)]
+pub struct Compose {
+    /// Compose file to use, defaults to compose.yaml (or docker-compose.yml) in the current
+    /// directory
+    #[arg(short, long, value_name = "FILE")]
+    pub file: Option<PathBuf>,
+
+    /// Project name, defaults to the name of the directory the compose file is in
+    #[arg(short, long)]
+    pub project_name: Option<String>,
+
+    #[clap(subcommand)]
+    pub command: ComposeOptions,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ComposeOptions {
+    /// Create And Start The Services, Dependencies First
+    Up,
+    /// Stop And Remove The Services' Containers And Their Network
+    Down(DownInfo),
+}
+
+#[derive(Debug, Args)]
+pub struct DownInfo {
+    /// Remove anonymous volumes associated with the containers
+    #[arg(short, long)]
+    pub volumes: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ComposeFile {
+    #[serde(default)]
+    services: BTreeMap<String, Service>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Service {
+    image: String,
+    container_name: Option<String>,
+    command: Option<ServiceCommand>,
+    #[serde(default)]
+    environment: Environment,
+    /// `HOST:CONTAINER[/PROTO]`, `IP:HOST:CONTAINER[/PROTO]`, or a bare container port to
+    /// publish on a random host port
+    #[serde(default)]
+    ports: Vec<String>,
+    /// `SOURCE:TARGET[:MODE]`, a source starting with `.` or `~` being a path relative to the
+    /// compose file's directory or the home directory, anything else a named volume
+    #[serde(default)]
+    volumes: Vec<String>,
+    #[serde(default)]
+    depends_on: DependsOn,
+    restart: Option<String>,
+}
+
+/// `command: npm start`, split like a shell would (quotes included), or
+/// `command: ["npm", "start"]`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ServiceCommand {
+    Shell(String),
+    Exec(Vec<String>),
+}
+
+/// `environment: ["KEY=value"]` or `environment: { KEY: value }`, where a key without a value
+/// is taken from the environment the CLI runs in.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Environment {
+    List(Vec<String>),
+    Map(BTreeMap<String, Option<serde_yaml::Value>>),
+}
+
+impl Default for Environment {
+    fn default() -> Environment {
+        Environment::List(Vec::new())
+    }
+}
+
+/// `depends_on: [db]`, or the long form keyed by service, whose conditions aren't supported and
+/// are treated as "started first".
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DependsOn {
+    List(Vec<String>),
+    Map(BTreeMap<String, serde_yaml::Value>),
+}
+
+impl Default for DependsOn {
+    fn default() -> DependsOn {
+        DependsOn::List(Vec::new())
+    }
+}
+
+impl DependsOn {
+    fn services(&self) -> Vec<&str> {
+        match self {
+            DependsOn::List(services) => services.iter().map(String::as_str).collect(),
+            DependsOn::Map(services) => services.keys().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl Service {
+    /// The command as the argument list `Config.Cmd` takes.
+    fn cmd(&self) -> Result<Option<Vec<String>>, String> {
+        match &self.command {
+            None => Ok(None),
+            Some(ServiceCommand::Exec(command)) => Ok(Some(command.clone())),
+            Some(ServiceCommand::Shell(command)) => shell_words::split(command)
+                .map(Some)
+                .map_err(|e| format!("invalid command {command:?}: {e}")),
+        }
+    }
+
+    /// `KEY=value` pairs, the shape `Config.Env` takes.
+    fn env(&self) -> Vec<String> {
+        match &self.environment {
+            Environment::List(variables) => variables.clone(),
+            Environment::Map(variables) => variables
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        Some(serde_yaml::Value::String(value)) => value.clone(),
+                        Some(serde_yaml::Value::Null) | None => {
+                            std::env::var(key).unwrap_or_default()
+                        }
+                        Some(value) => serde_yaml::to_string(value)
+                            .unwrap_or_default()
+                            .trim_end()
+                            .to_string(),
+                    };
+                    format!("{key}={value}")
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A compose file loaded and checked, along with the project it belongs to.
+struct Project {
+    name: String,
+    /// The directory of the compose file, which relative bind mounts start from
+    dir: PathBuf,
+    services: BTreeMap<String, Service>,
+    /// Service names with every service after the ones it depends on
+    order: Vec<String>,
+}
+
+impl Project {
+    fn network(&self) -> String {
+        format!("{}_default", self.name)
+    }
+
+    fn container_name(&self, service: &str) -> String {
+        self.services[service]
+            .container_name
+            .clone()
+            .unwrap_or_else(|| format!("{}-{service}-1", self.name))
+    }
+}
+
+/// Finds and reads the compose file, checking its ports, restart policies and dependencies up
+/// front so nothing is created from a file that can't be brought up completely.
+fn load_project(compose: &Compose) -> Result<Project, CliError> {
+    let path = match &compose.file {
+        Some(path) => path.clone(),
+        None => DEFAULT_FILES
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.exists())
+            .ok_or_else(|| CliError::Spec {
+                path: DEFAULT_FILES[0].to_string(),
+                reason: "no compose file in the current directory (pass --file)".to_string(),
+            })?,
+    };
+    let error = |reason: String| CliError::Spec {
+        path: path.display().to_string(),
+        reason,
+    };
+
+    let contents = std::fs::read_to_string(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => error("no such file".to_string()),
+        _ => error(e.to_string()),
+    })?;
+    let ComposeFile { services } =
+        serde_yaml::from_str(&contents).map_err(|e| error(e.to_string()))?;
+    if services.is_empty() {
+        return Err(error("no services defined".to_string()));
+    }
+
+    for (name, service) in &services {
+        service
+            .cmd()
+            .map_err(|e| error(format!("service {name:?}: {e}")))?;
+        for port in &service.ports {
+            publish(port).map_err(|e| error(format!("service {name:?}: {e}")))?;
+        }
+        if let Some(restart) = &service.restart {
+            parse_restart_policy(restart).map_err(|e| error(format!("service {name:?}: {e}")))?;
+        }
+    }
+    let order = start_order(&services).map_err(error)?;
+
+    let dir = std::fs::canonicalize(&path)
+        .map_err(|e| error(e.to_string()))?
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let name = match &compose.project_name {
+        Some(name) => name.clone(),
+        None => project_name(&dir),
+    };
+    Ok(Project {
+        name,
+        dir,
+        services,
+        order,
+    })
+}
+
+/// The compose file's directory name, lowercased and cut down to the characters Compose allows
+/// in project names.
+fn project_name(dir: &Path) -> String {
+    let dir = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let name: String = dir
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    if name.is_empty() {
+        "default".to_string()
+    } else {
+        name
+    }
+}
+
+/// Parses a `ports` entry, a bare container port getting a random host port.
+fn publish(port: &str) -> Result<crate::run::PortMapping, String> {
+    if port.contains(':') {
+        parse_port(port)
+    } else {
+        parse_port(&format!(":{port}"))
+    }
+}
+
+/// Orders the services so each one comes after everything in its `depends_on`, alphabetically
+/// where the dependencies leave a choice.
+fn start_order(services: &BTreeMap<String, Service>) -> Result<Vec<String>, String> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Visiting,
+        Done,
+    }
+
+    fn visit<'a>(
+        name: &'a str,
+        services: &'a BTreeMap<String, Service>,
+        marks: &mut HashMap<&'a str, Mark>,
+        path: &mut Vec<&'a str>,
+        order: &mut Vec<String>,
+    ) -> Result<(), String> {
+        match marks.get(name) {
+            Some(Mark::Done) => return Ok(()),
+            Some(Mark::Visiting) => {
+                path.push(name);
+                let start = path
+                    .iter()
+                    .position(|service| *service == name)
+                    .unwrap_or(0);
+                return Err(format!("dependency cycle: {}", path[start..].join(" -> ")));
+            }
+            None => {}
+        }
+
+        marks.insert(name, Mark::Visiting);
+        path.push(name);
+        for dependency in services[name].depends_on.services() {
+            if !services.contains_key(dependency) {
+                return Err(format!(
+                    "service {name:?} depends on undefined service {dependency:?}"
+                ));
+            }
+            visit(dependency, services, marks, path, order)?;
+        }
+        path.pop();
+        marks.insert(name, Mark::Done);
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    let mut marks = HashMap::new();
+    let mut order = Vec::new();
+    for name in services.keys() {
+        visit(name, services, &mut marks, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+/// Turns a relative bind source (`./data:/data`, `~/conf:/etc/app`) into the absolute path the
+/// daemon wants, relative paths starting from `dir`. Named volumes and absolute paths are left
+/// as they are.
+fn resolve_bind(volume: &str, dir: &Path) -> String {
+    let Some((source, rest)) = volume.split_once(':') else {
+        return volume.to_string();
+    };
+
+    let base = match source {
+        "." | ".." => dir.to_path_buf(),
+        _ if source.starts_with("./") || source.starts_with("../") => dir.to_path_buf(),
+        "~" => home_dir(),
+        _ if source.starts_with("~/") => home_dir(),
+        _ => return volume.to_string(),
+    };
+    let relative = source.strip_prefix('~').unwrap_or(source);
+
+    // Cleaned up lexically, the daemon doesn't take `.` or `..` in bind sources
+    let mut resolved = base;
+    for component in Path::new(relative).components() {
+        match component {
+            std::path::Component::Normal(part) => resolved.push(part),
+            std::path::Component::ParentDir => {
+                resolved.pop();
+            }
+            _ => {}
+        }
+    }
+    format!("{}:{rest}", resolved.display())
+}
+
+fn home_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+/// Creates the project network unless it exists already.
+async fn ensure_network(docker: &Docker, project: &Project) -> Result<(), CliError> {
+    let network = project.network();
+    match docker
+        .inspect_network(&network, None::<InspectNetworkOptions<String>>)
+        .await
+    {
+        Ok(_) => return Ok(()),
+        Err(DockerResponseServerError {
+            status_code: 404, ..
+        }) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let options = CreateNetworkOptions {
+        name: network.clone(),
+        driver: "bridge".to_string(),
+        check_duplicate: true,
+        labels: HashMap::from([(PROJECT_LABEL.to_string(), project.name.clone())]),
+        ..Default::default()
+    };
+    docker.create_network(options).await?;
+    Ok(println!("Created Network: {network}"))
+}
+
+/// Pulls `image` unless the daemon has it already, since creating a container doesn't.
+async fn ensure_image(docker: &Docker, image: &str) -> Result<(), CliError> {
+    match docker.inspect_image(image).await {
+        Ok(_) => return Ok(()),
+        Err(DockerResponseServerError {
+            status_code: 404, ..
+        }) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    eprintln!("Pulling {image}");
+    let (from_image, tag) = split_image_ref(image);
+    let options = CreateImageOptions {
+        from_image,
+        tag,
+        ..Default::default()
+    };
+    let mut pull_stream = docker.create_image(Some(options), None, None);
+    while let Some(info) = pull_stream.next().await {
+        if let CreateImageInfo {
+            error: Some(error), ..
+        } = info?
+        {
+            return Err(CliError::Daemon(error));
+        }
+    }
+    Ok(())
+}
+
+/// The create request for one service's container.
+fn container_config(project: &Project, name: &str) -> Config<String> {
+    let service = &project.services[name];
+    let network = project.network();
+    // Checked when the file was loaded
+    let publish: Vec<_> = service
+        .ports
+        .iter()
+        .filter_map(|port| publish(port).ok())
+        .collect();
+
+    let host_config = HostConfig {
+        binds: (!service.volumes.is_empty()).then(|| {
+            service
+                .volumes
+                .iter()
+                .map(|volume| resolve_bind(volume, &project.dir))
+                .collect()
+        }),
+        port_bindings: (!publish.is_empty()).then(|| port_bindings(&publish)),
+        restart_policy: service
+            .restart
+            .as_deref()
+            .and_then(|restart| parse_restart_policy(restart).ok()),
+        network_mode: Some(network.clone()),
+        ..Default::default()
+    };
+
+    // Other services reach this one by its service name
+    let endpoint = EndpointSettings {
+        aliases: Some(vec![name.to_string()]),
+        ..Default::default()
+    };
+
+    let env = service.env();
+    Config {
+        image: Some(service.image.clone()),
+        // Checked when the file was loaded
+        cmd: service.cmd().unwrap_or_default(),
+        env: (!env.is_empty()).then_some(env),
+        exposed_ports: (!publish.is_empty()).then(|| exposed_ports(&publish)),
+        labels: Some(HashMap::from([
+            (PROJECT_LABEL.to_string(), project.name.clone()),
+            (SERVICE_LABEL.to_string(), name.to_string()),
+        ])),
+        host_config: Some(host_config),
+        networking_config: Some(NetworkingConfig {
+            endpoints_config: HashMap::from([(network, endpoint)]),
+        }),
+        ..Default::default()
+    }
+}
+
+// ./exe compose [-f <file>] [-p <project>] up|down [-v]
+pub async fn run(docker: &Docker, compose: &Compose) -> Result<(), CliError> {
+    let project = load_project(compose)?;
+
+    match &compose.command {
+        ComposeOptions::Up => {
+            ensure_network(docker, &project).await?;
+
+            for name in &project.order {
+                let container_name = project.container_name(name);
+
+                // Containers left from an earlier `up` are started again rather than recreated
+                match docker
+                    .inspect_container(&container_name, None::<InspectContainerOptions>)
+                    .await
+                {
+                    Ok(existing) => {
+                        if existing.state.and_then(|state| state.running) == Some(true) {
+                            println!("Running: {container_name}");
+                            continue;
+                        }
+                    }
+                    Err(DockerResponseServerError {
+                        status_code: 404, ..
+                    }) => {
+                        ensure_image(docker, &project.services[name].image).await?;
+                        let options = CreateContainerOptions {
+                            name: container_name.as_str(),
+                            platform: None,
+                        };
+                        let created = docker
+                            .create_container(Some(options), container_config(&project, name))
+                            .await?;
+                        for warning in created.warnings.iter().filter(|w| !w.is_empty()) {
+                            eprintln!("Warning: {warning}");
+                        }
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+
+                docker
+                    .start_container(&container_name, None::<StartContainerOptions<String>>)
+                    .await?;
+                println!("Started: {container_name}");
+            }
+            Ok(())
+        }
+        ComposeOptions::Down(DownInfo { volumes }) => {
+            let options = ListContainersOptions {
+                all: true,
+                filters: HashMap::from([(
+                    "label".to_string(),
+                    vec![format!("{PROJECT_LABEL}={}", project.name)],
+                )]),
+                ..Default::default()
+            };
+            let mut containers = docker.list_containers(Some(options)).await?;
+
+            // Dependents go first, and containers of services no longer in the file before those
+            let position = |container: &ContainerSummary| {
+                container
+                    .labels
+                    .as_ref()
+                    .and_then(|labels| labels.get(SERVICE_LABEL))
+                    .and_then(|service| project.order.iter().position(|name| name == service))
+                    .map_or(usize::MAX, |position| position)
+            };
+            containers.sort_by_key(|container| std::cmp::Reverse(position(container)));
+
+            let mut failed = Failures::default();
+            for container in &containers {
+                let Some(id) = container.id.as_deref() else {
+                    continue;
+                };
+                let name = container
+                    .names
+                    .as_ref()
+                    .and_then(|names| names.first())
+                    .map_or(id, |name| name.trim_start_matches('/'));
+
+                let removed = async {
+                    // bollard reports the 304 for an already stopped container as success
+                    docker
+                        .stop_container(id, None::<StopContainerOptions>)
+                        .await?;
+                    let options = RemoveContainerOptions {
+                        v: *volumes,
+                        ..Default::default()
+                    };
+                    docker.remove_container(id, Some(options)).await
+                };
+                match removed.await {
+                    Ok(()) => println!("Removed: {name}"),
+                    Err(e) => {
+                        eprintln!("Failed to remove container {name:?}: {e}");
+                        failed.record(e);
+                    }
+                }
+            }
+
+            let network = project.network();
+            match docker.remove_network(&network).await {
+                Ok(()) => println!("Removed Network: {network}"),
+                Err(DockerResponseServerError {
+                    status_code: 404, ..
+                }) => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            failed.finish("container", "be removed")?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_bind_makes_relative_sources_absolute() {
+        let dir = Path::new("/srv/app");
+        assert_eq!(resolve_bind("./data:/data", dir), "/srv/app/data:/data");
+        assert_eq!(resolve_bind(".:/src:ro", dir), "/srv/app:/src:ro");
+        assert_eq!(
+            resolve_bind("../shared/./conf:/etc/app", dir),
+            "/srv/shared/conf:/etc/app"
+        );
+    }
+
+    #[test]
+    fn resolve_bind_leaves_volumes_and_absolute_paths_alone() {
+        let dir = Path::new("/srv/app");
+        assert_eq!(
+            resolve_bind("pgdata:/var/lib/postgresql/data", dir),
+            "pgdata:/var/lib/postgresql/data"
+        );
+        assert_eq!(resolve_bind("/var/log:/logs", dir), "/var/log:/logs");
+        assert_eq!(resolve_bind("/cache", dir), "/cache");
+    }
+
+    #[test]
+    fn string_commands_are_split_like_a_shell() {
+        let service: Service =
+            serde_yaml::from_str(r#"{ image: alpine, command: "sh -c 'echo hello world'" }"#)
+                .unwrap();
+        assert_eq!(
+            service.cmd().unwrap().unwrap(),
+            ["sh", "-c", "echo hello world"]
+        );
+
+        let unbalanced: Service =
+            serde_yaml::from_str(r#"{ image: alpine, command: "echo 'oops" }"#).unwrap();
+        assert!(unbalanced.cmd().is_err());
+    }
+}
@@ -0,0 +1,78 @@
+//! Defaults loaded from the `--config` file
+//!
+//! Settings are resolved in this order, first match wins:
+//!
+//! 1. flags given on the command line
+//! 2. the config file
+//! 3. environment variables (`DOCKER_HOST`, `DOCKER_TLS_VERIFY`, `DOCKER_CERT_PATH`)
+//! 4. built-in defaults
+//!
+//! The file is read as JSON when it ends in `.json` and as TOML otherwise, e.g.
+//!
+//! ```toml
+//! host = "tcp://build-box:2376"
+//! tlsverify = true
+//! cert-path = "/etc/docker/certs"
+//! format = "json"
+//! filters = ["label=team=platform"]
+//! ```
+
+use serde::{Deserialize, Deserializer};
+use std::path::{Path, PathBuf};
+
+use crate::error::CliError;
+use crate::{parse_filter, OutputFormat};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Docker daemon to connect to, as for `--host`
+    pub host: Option<String>,
+    /// Use TLS and verify the remote daemon, as for `--tlsverify`
+    pub tlsverify: Option<bool>,
+    /// Directory holding key.pem, cert.pem and ca.pem, as for `--cert-path`
+    pub cert_path: Option<PathBuf>,
+    /// Path to the TLS client certificate, as for `--tlscert`
+    pub tlscert: Option<PathBuf>,
+    /// Path to the TLS client key, as for `--tlskey`
+    pub tlskey: Option<PathBuf>,
+    /// Path to the TLS certificate authority, as for `--tlscacert`
+    pub tlscacert: Option<PathBuf>,
+    /// Output format used when `--format` isn't given
+    pub format: Option<OutputFormat>,
+    /// `key=value` filters applied to `ps info` when no `--filter` is given
+    #[serde(deserialize_with = "deserialize_filters")]
+    pub filters: Vec<(String, String)>,
+}
+
+/// Reads `filters` as `key=value` strings, the same shape `--filter` takes.
+fn deserialize_filters<'de, D>(deserializer: D) -> Result<Vec<(String, String)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|filter| parse_filter(filter).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+impl Config {
+    /// Reads and parses the config file at `path`.
+    pub fn load(path: &Path) -> Result<Config, CliError> {
+        let error = |reason: String| CliError::Config {
+            path: path.display().to_string(),
+            reason,
+        };
+
+        let contents = std::fs::read_to_string(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => error("no such file".to_string()),
+            _ => error(e.to_string()),
+        })?;
+
+        if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&contents).map_err(|e| error(e.to_string()))
+        } else {
+            toml::from_str(&contents).map_err(|e| error(e.to_string()))
+        }
+    }
+}
@@ -0,0 +1,458 @@
+//! Choosing and opening the connection to the Docker daemon
+
+use clap::Args;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bollard::{Docker, API_DEFAULT_VERSION};
+
+use crate::context;
+use crate::error::CliError;
+
+/// Request timeout in seconds, matching bollard's own connection defaults.
+pub const DEFAULT_TIMEOUT: u64 = 120;
+
+/// Seconds to wait for the first ping, so an unreachable host fails fast.
+pub const CONNECT_TIMEOUT: u64 = 5;
+
+/// Where the daemon listens when no host, socket or context is given.
+pub const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+// Everything that decides which daemon to talk to and how. A plain comment, since a doc comment
+// on a flattened struct would replace the command's about text.
+#[derive(Debug, Default, Args)]
+pub struct ConnectOpts {
+    /// Docker daemon to connect to (e.g. tcp://1.2.3.4:2375), defaults to the local unix socket
+    #[arg(short = 'H', long, env = "DOCKER_HOST", value_name = "HOST")]
+    pub host: Option<String>,
+
+    /// Unix socket of the daemon (e.g. $XDG_RUNTIME_DIR/docker.sock for rootless Docker or a
+    /// Podman socket), overrides the host and context
+    #[arg(long, value_name = "PATH")]
+    pub socket: Option<PathBuf>,
+
+    /// Name of the docker context to use (see `context ls`), ignored when -H/--host is given.
+    /// Given as a flag it wins over DOCKER_HOST and the config file's host
+    #[arg(long, env = "DOCKER_CONTEXT", value_name = "NAME")]
+    pub context: Option<String>,
+
+    /// Set when `--context` was on the command line but the host only came from `DOCKER_HOST`
+    /// or the config file, which makes the context win
+    #[arg(skip)]
+    pub context_over_host: bool,
+
+    /// Give up on the command after this many seconds
+    #[arg(long, global = true, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Retry reads (listings, inspects) this many times when the daemon fails transiently
+    #[arg(long, global = true, value_name = "N", default_value_t = 2)]
+    pub retries: u32,
+
+    /// Milliseconds to wait before the first retry, doubling for each one after
+    #[arg(long, global = true, value_name = "MS", default_value_t = 200)]
+    pub retry_delay: u64,
+
+    /// Use TLS and verify the remote daemon
+    #[arg(long, env = "DOCKER_TLS_VERIFY")]
+    pub tlsverify: bool,
+
+    /// Directory holding key.pem, cert.pem and ca.pem, defaults to ~/.docker
+    #[arg(long, env = "DOCKER_CERT_PATH", value_name = "DIR")]
+    pub cert_path: Option<PathBuf>,
+
+    /// Path to the TLS client certificate
+    #[arg(long, value_name = "FILE")]
+    pub tlscert: Option<PathBuf>,
+
+    /// Path to the TLS client key
+    #[arg(long, value_name = "FILE")]
+    pub tlskey: Option<PathBuf>,
+
+    /// Path to the TLS certificate authority
+    #[arg(long, value_name = "FILE")]
+    pub tlscacert: Option<PathBuf>,
+}
+
+/// Client certificate files used to talk to a TLS-secured daemon.
+pub struct TlsPaths {
+    pub key: PathBuf,
+    pub cert: PathBuf,
+    pub ca: PathBuf,
+}
+
+/// How the client ends up talking to the daemon.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Endpoint {
+    /// A unix socket at this path
+    Unix(String),
+    /// Plain HTTP to a `tcp://` or `http://` host
+    Http(String),
+    /// TLS to this host, defaulting to `tcp://localhost:2376`
+    Tls(String),
+}
+
+/// How transient failures of idempotent requests are retried.
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    pub retries: u32,
+    pub delay: Duration,
+}
+
+impl Retry {
+    /// Runs the request `op` builds, retrying with exponential backoff while it fails in a way
+    /// that may go away. Only for reads: a mutating request that failed midway may still have
+    /// taken effect.
+    pub async fn run<T, F, Fut>(
+        self,
+        operation: &str,
+        mut op: F,
+    ) -> Result<T, bollard::errors::Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, bollard::errors::Error>>,
+    {
+        let mut delay = self.delay;
+        let mut attempt = 0;
+        loop {
+            match op().await {
+                Err(e) if attempt < self.retries && is_transient(&e) => {
+                    attempt += 1;
+                    tracing::debug!(operation, attempt, error = %e, ?delay, "retrying");
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Dropped connections, timeouts and 5xx gateway-ish answers, as opposed to errors that will
+/// come back the same on every attempt.
+fn is_transient(error: &bollard::errors::Error) -> bool {
+    use bollard::errors::Error;
+
+    matches!(
+        error,
+        Error::DockerResponseServerError {
+            status_code: 500 | 502 | 503 | 504,
+            ..
+        } | Error::HyperResponseError { .. }
+            | Error::HyperLegacyError { .. }
+            | Error::IOError { .. }
+            | Error::RequestTimeoutError
+    )
+}
+
+impl ConnectOpts {
+    /// The retry policy given by `--retries` and `--retry-delay`.
+    pub fn retry(&self) -> Retry {
+        Retry {
+            retries: self.retries,
+            delay: Duration::from_millis(self.retry_delay),
+        }
+    }
+
+    /// The host to use instead of a context, if any.
+    fn host(&self) -> Option<&str> {
+        self.host.as_deref().filter(|_| !self.context_over_host)
+    }
+
+    /// Works out which daemon to talk to: `--socket` wins, then an explicit host, then the active
+    /// docker context, then the local socket.
+    pub fn endpoint(&self) -> Result<(Endpoint, Option<TlsPaths>), CliError> {
+        if let Some(socket) = &self.socket {
+            return Ok((Endpoint::Unix(socket.display().to_string()), None));
+        }
+
+        let (host, tls) = match self.host() {
+            Some(host) => (Some(host.to_string()), self.tls_paths()),
+            None => match context::resolve(self.context.as_deref())? {
+                Some(context) => {
+                    let tls = context.tls_paths().or_else(|| self.tls_paths());
+                    (Some(context.host), tls)
+                }
+                None => (None, self.tls_paths()),
+            },
+        };
+
+        let endpoint = match host {
+            _ if tls.is_some() => {
+                Endpoint::Tls(host.unwrap_or_else(|| "tcp://localhost:2376".to_string()))
+            }
+            None => Endpoint::Unix(DEFAULT_SOCKET.to_string()),
+            Some(host) => match host.strip_prefix("unix://") {
+                Some(path) => Endpoint::Unix(path.to_string()),
+                None if host.starts_with("tcp://") || host.starts_with("http://") => {
+                    Endpoint::Http(host)
+                }
+                None => {
+                    return Err(
+                        bollard::errors::Error::UnsupportedURISchemeError { uri: host }.into(),
+                    )
+                }
+            },
+        };
+        Ok((endpoint, tls))
+    }
+
+    /// Resolves the TLS certificate paths, or `None` when TLS wasn't asked for.
+    ///
+    /// Explicit `--tls*` flags win over the files found in `DOCKER_CERT_PATH` (or `~/.docker`).
+    fn tls_paths(&self) -> Option<TlsPaths> {
+        let https = self.host().is_some_and(|host| host.starts_with("https://"));
+        let flagged = self.tlscert.is_some() || self.tlskey.is_some() || self.tlscacert.is_some();
+        if !(self.tlsverify || https || flagged) {
+            return None;
+        }
+
+        let cert_dir = self.cert_path.clone().unwrap_or_else(|| {
+            std::env::var_os("HOME")
+                .map(PathBuf::from)
+                .unwrap_or_default()
+                .join(".docker")
+        });
+
+        Some(TlsPaths {
+            key: self
+                .tlskey
+                .clone()
+                .unwrap_or_else(|| cert_dir.join("key.pem")),
+            cert: self
+                .tlscert
+                .clone()
+                .unwrap_or_else(|| cert_dir.join("cert.pem")),
+            ca: self
+                .tlscacert
+                .clone()
+                .unwrap_or_else(|| cert_dir.join("ca.pem")),
+        })
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Endpoint::Unix(path) => write!(f, "unix://{path}"),
+            Endpoint::Http(host) | Endpoint::Tls(host) => write!(f, "{host}"),
+        }
+    }
+}
+
+/// Opens a client for the daemon `opts` select. Nothing is sent yet, see [`ping`].
+pub fn connect(opts: &ConnectOpts) -> Result<Docker, CliError> {
+    let (endpoint, tls) = opts.endpoint()?;
+    let timeout = opts.timeout.unwrap_or(DEFAULT_TIMEOUT);
+    tracing::info!(%endpoint, tls = tls.is_some(), "connecting to the Docker daemon");
+
+    let docker = match (&endpoint, tls) {
+        (Endpoint::Tls(host), Some(TlsPaths { key, cert, ca })) => {
+            let missing: Vec<String> = [&key, &cert, &ca]
+                .into_iter()
+                .filter(|path| !path.exists())
+                .map(|path| path.display().to_string())
+                .collect();
+            if !missing.is_empty() {
+                return Err(CliError::MissingCerts(missing));
+            }
+
+            Docker::connect_with_ssl(host, &key, &cert, &ca, timeout, API_DEFAULT_VERSION)?
+        }
+        (Endpoint::Unix(path), _) => {
+            check_socket(path)?;
+            Docker::connect_with_unix(path, timeout, API_DEFAULT_VERSION)?
+        }
+        (Endpoint::Http(host) | Endpoint::Tls(host), _) => {
+            Docker::connect_with_http(host, timeout, API_DEFAULT_VERSION)?
+        }
+    };
+    Ok(docker.with_timeout(Duration::from_secs(timeout)))
+}
+
+/// Fails early with a readable message rather than on the first API call, and fast when the
+/// host doesn't answer at all.
+pub async fn ping(docker: &Docker, opts: &ConnectOpts) -> Result<(), CliError> {
+    let connect_timeout = CONNECT_TIMEOUT.min(opts.timeout.unwrap_or(CONNECT_TIMEOUT));
+    let pinged = tokio::time::timeout(Duration::from_secs(connect_timeout), docker.ping())
+        .await
+        .unwrap_or(Err(bollard::errors::Error::RequestTimeoutError));
+
+    match pinged {
+        Ok(_) => Ok(()),
+        Err(source) => {
+            tracing::debug!(error = ?source, "ping failed");
+            let host = opts.endpoint()?.0.to_string();
+            Err(CliError::Connection { host, source })
+        }
+    }
+}
+
+/// Makes sure `path` is a unix socket before connecting, so a typo or a daemon that isn't
+/// running gives a clear message instead of a hyper error.
+fn check_socket(path: &str) -> Result<(), CliError> {
+    let error = |reason: &str| CliError::Socket {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(error("no such file, is the daemon running?"))
+        }
+        Err(e) => return Err(error(&e.to_string())),
+    };
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if !metadata.file_type().is_socket() {
+            return Err(error("not a socket"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(opts: &ConnectOpts) -> Endpoint {
+        opts.endpoint().unwrap().0
+    }
+
+    #[test]
+    fn socket_wins_over_host() {
+        let opts = ConnectOpts {
+            socket: Some(PathBuf::from("/run/user/1000/docker.sock")),
+            host: Some("tcp://10.0.0.1:2375".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            endpoint(&opts),
+            Endpoint::Unix("/run/user/1000/docker.sock".to_string())
+        );
+    }
+
+    #[test]
+    fn unix_host_gives_unix_endpoint() {
+        let opts = ConnectOpts {
+            host: Some("unix:///tmp/docker.sock".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            endpoint(&opts),
+            Endpoint::Unix("/tmp/docker.sock".to_string())
+        );
+    }
+
+    #[test]
+    fn tcp_host_gives_http_endpoint() {
+        let opts = ConnectOpts {
+            host: Some("tcp://10.0.0.1:2375".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            endpoint(&opts),
+            Endpoint::Http("tcp://10.0.0.1:2375".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_scheme_is_rejected() {
+        let opts = ConnectOpts {
+            host: Some("ssh://me@box".to_string()),
+            ..Default::default()
+        };
+        assert!(opts.endpoint().is_err());
+    }
+
+    #[test]
+    fn tlsverify_uses_cert_path() {
+        let opts = ConnectOpts {
+            host: Some("tcp://10.0.0.1:2376".to_string()),
+            tlsverify: true,
+            cert_path: Some(PathBuf::from("/certs")),
+            tlscacert: Some(PathBuf::from("/other/ca.pem")),
+            ..Default::default()
+        };
+        let (endpoint, tls) = opts.endpoint().unwrap();
+        assert_eq!(endpoint, Endpoint::Tls("tcp://10.0.0.1:2376".to_string()));
+        let tls = tls.unwrap();
+        assert_eq!(tls.key, PathBuf::from("/certs/key.pem"));
+        assert_eq!(tls.cert, PathBuf::from("/certs/cert.pem"));
+        assert_eq!(tls.ca, PathBuf::from("/other/ca.pem"));
+    }
+
+    #[test]
+    fn https_host_implies_tls() {
+        let opts = ConnectOpts {
+            host: Some("https://10.0.0.1:2376".to_string()),
+            cert_path: Some(PathBuf::from("/certs")),
+            ..Default::default()
+        };
+        assert!(matches!(endpoint(&opts), Endpoint::Tls(_)));
+    }
+
+    /// The context cases share one test, since they point `DOCKER_CONFIG` at a scratch
+    /// directory for the whole process.
+    #[test]
+    fn contexts() {
+        let dir = std::env::temp_dir().join(format!("locci-contexts-{}", std::process::id()));
+        let meta = dir.join("contexts/meta/0123abcd");
+        std::fs::create_dir_all(&meta).unwrap();
+        std::fs::write(
+            meta.join("meta.json"),
+            r#"{"Name":"remote","Endpoints":{"docker":{"Host":"tcp://remote:2375"}}}"#,
+        )
+        .unwrap();
+        std::env::set_var("DOCKER_CONFIG", &dir);
+
+        // No host, no context: the local socket
+        assert_eq!(
+            endpoint(&ConnectOpts::default()),
+            Endpoint::Unix(DEFAULT_SOCKET.to_string())
+        );
+
+        let context = ConnectOpts {
+            context: Some("remote".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            endpoint(&context),
+            Endpoint::Http("tcp://remote:2375".to_string())
+        );
+
+        let unknown = ConnectOpts {
+            context: Some("nope".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            unknown.endpoint(),
+            Err(CliError::UnknownContext(name)) if name == "nope"
+        ));
+
+        // DOCKER_HOST wins over DOCKER_CONTEXT, but not over a --context on the command line
+        let env_host = ConnectOpts {
+            host: Some("tcp://env:2375".to_string()),
+            context: Some("remote".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            endpoint(&env_host),
+            Endpoint::Http("tcp://env:2375".to_string())
+        );
+        let context_flag = ConnectOpts {
+            context_over_host: true,
+            ..env_host
+        };
+        assert_eq!(
+            endpoint(&context_flag),
+            Endpoint::Http("tcp://remote:2375".to_string())
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
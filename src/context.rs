@@ -0,0 +1,206 @@
+//! Docker CLI contexts, as managed by `docker context`
+
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use prettytable::{row, Cell, Row};
+
+use crate::connection::TlsPaths;
+use crate::error::CliError;
+use crate::{print_json_list, OutputArgs};
+
+/// The built-in context that talks to the local daemon.
+const DEFAULT_CONTEXT: &str = "default";
+
+#[derive(Debug, Args)]
+pub struct Context {
+    #[clap(subcommand)]
+    pub command: ContextOptions,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ContextOptions {
+    /// List The Available Contexts
+    Ls,
+}
+
+/// A context stored under `~/.docker/contexts`.
+#[derive(Debug, serde::Serialize)]
+pub struct DockerContext {
+    pub name: String,
+    pub description: String,
+    pub host: String,
+    /// Holds ca.pem, cert.pem and key.pem when the context was created with TLS material
+    #[serde(skip)]
+    pub tls_dir: Option<PathBuf>,
+}
+
+impl DockerContext {
+    /// The certificate files of a TLS context.
+    pub fn tls_paths(&self) -> Option<TlsPaths> {
+        self.tls_dir.as_ref().map(|dir| TlsPaths {
+            key: dir.join("key.pem"),
+            cert: dir.join("cert.pem"),
+            ca: dir.join("ca.pem"),
+        })
+    }
+}
+
+/// `contexts/meta/<id>/meta.json`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContextMeta {
+    name: String,
+    #[serde(default)]
+    metadata: Option<ContextMetadata>,
+    #[serde(default)]
+    endpoints: std::collections::HashMap<String, ContextEndpoint>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContextMetadata {
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContextEndpoint {
+    host: Option<String>,
+}
+
+/// `$DOCKER_CONFIG`, falling back to `~/.docker`.
+pub fn docker_config_dir() -> PathBuf {
+    std::env::var_os("DOCKER_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            std::env::var_os("HOME")
+                .map(PathBuf::from)
+                .unwrap_or_default()
+                .join(".docker")
+        })
+}
+
+/// Reads every stored context, sorted by name. Contexts without a docker endpoint are skipped.
+pub fn load_contexts() -> Result<Vec<DockerContext>, CliError> {
+    let dir = docker_config_dir().join("contexts");
+    let entries = match std::fs::read_dir(dir.join("meta")) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut contexts = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let meta_file = entry.path().join("meta.json");
+        let meta: ContextMeta = match std::fs::read_to_string(&meta_file) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let Some(host) = meta
+            .endpoints
+            .get("docker")
+            .and_then(|endpoint| endpoint.host.clone())
+        else {
+            continue;
+        };
+
+        // TLS material lives in a sibling tree keyed by the same directory name
+        let tls_dir = dir.join("tls").join(entry.file_name()).join("docker");
+        contexts.push(DockerContext {
+            name: meta.name,
+            description: meta.metadata.unwrap_or_default().description,
+            host,
+            tls_dir: tls_dir.join("ca.pem").exists().then_some(tls_dir),
+        });
+    }
+
+    contexts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(contexts)
+}
+
+/// The context in use: `--context`/`DOCKER_CONTEXT` when given, otherwise `currentContext` from
+/// the CLI's config.json, otherwise `default`.
+pub fn current_context_name(explicit: Option<&str>) -> String {
+    if let Some(name) = explicit {
+        return name.to_string();
+    }
+
+    #[derive(Deserialize)]
+    struct CliConfig {
+        #[serde(rename = "currentContext")]
+        current_context: Option<String>,
+    }
+
+    std::fs::read_to_string(docker_config_dir().join("config.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<CliConfig>(&contents).ok())
+        .and_then(|config| config.current_context)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| DEFAULT_CONTEXT.to_string())
+}
+
+/// Looks the active context up, `None` meaning the default local daemon.
+pub fn resolve(explicit: Option<&str>) -> Result<Option<DockerContext>, CliError> {
+    let name = current_context_name(explicit);
+    if name == DEFAULT_CONTEXT {
+        return Ok(None);
+    }
+
+    load_contexts()?
+        .into_iter()
+        .find(|context| context.name == name)
+        .map(Some)
+        .ok_or(CliError::UnknownContext(name))
+}
+
+// ./exe context ls
+pub fn run(
+    command: &ContextOptions,
+    explicit: Option<&str>,
+    output: &OutputArgs,
+) -> Result<(), CliError> {
+    match command {
+        ContextOptions::Ls => {
+            let contexts = load_contexts()?;
+            let current = current_context_name(explicit);
+
+            if output.is_json() {
+                return print_json_list(output, &contexts);
+            }
+
+            // Context Summary table
+            let mut context_summary_table =
+                output.table(row![b->"Name", b->"Description", b->"Docker Endpoint"]);
+
+            let marker = |name: &str| if name == current { " *" } else { "" };
+            context_summary_table.add_row(Row::new(vec![
+                Cell::new(&format!("{DEFAULT_CONTEXT}{}", marker(DEFAULT_CONTEXT))),
+                Cell::new("Current DOCKER_HOST based configuration"),
+                Cell::new("unix:///var/run/docker.sock"),
+            ]));
+            for DockerContext {
+                name,
+                description,
+                host,
+                ..
+            } in &contexts
+            {
+                context_summary_table.add_row(Row::new(vec![
+                    Cell::new(&format!("{name}{}", marker(name))),
+                    Cell::new(description),
+                    Cell::new(host),
+                ]));
+            }
+
+            context_summary_table.printstd();
+            Ok(())
+        }
+    }
+}
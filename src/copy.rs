@@ -0,0 +1,162 @@
+//! Moving files in and out of containers, as the tar archives the daemon's archive API speaks
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::CliError;
+
+/// One side of `ps cp`.
+#[derive(Debug, Clone)]
+pub enum CopyPath {
+    /// A path on this machine
+    Local(PathBuf),
+    /// `container:/path`
+    Container { container: String, path: String },
+}
+
+/// Parses `container:/path` or a local path. Like the Docker CLI, anything starting with `/` or
+/// `.` is local, so local files with a colon in their name can be given as `./a:b`.
+pub fn parse_copy_path(arg: &str) -> Result<CopyPath, String> {
+    if arg.starts_with(['/', '.']) {
+        return Ok(CopyPath::Local(PathBuf::from(arg)));
+    }
+
+    match arg.split_once(':') {
+        Some((container, _)) if container.is_empty() => {
+            Err(format!("{arg:?} is missing the container name"))
+        }
+        Some((_, "")) => Err(format!("{arg:?} is missing the path in the container")),
+        Some((container, path)) if !container.contains('/') => Ok(CopyPath::Container {
+            container: container.to_string(),
+            path: path.to_string(),
+        }),
+        _ => Ok(CopyPath::Local(PathBuf::from(arg))),
+    }
+}
+
+/// Extracts an archive downloaded from a container to `dest`. When `dest` is an existing
+/// directory the copied file or directory lands inside it, otherwise it's created as `dest`.
+pub fn unpack(archive: &[u8], dest: &Path) -> Result<(), CliError> {
+    let into_dir = dest.is_dir();
+    let mut archive = tar::Archive::new(archive);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(CliError::Copy(format!(
+                "refusing to unpack {} outside of {}",
+                path.display(),
+                dest.display()
+            )));
+        }
+
+        // The archive's top level entry carries the source's own name, which gets replaced
+        // by `dest` when copying to a new path
+        let rest: PathBuf = path.components().skip(1).collect();
+        let out = if into_dir {
+            dest.join(&path)
+        } else if rest.as_os_str().is_empty() {
+            dest.to_path_buf()
+        } else {
+            dest.join(rest)
+        };
+
+        // A symlink unpacked earlier (`x -> /etc`) would carry `x/passwd` out of `dest`
+        let mut dir = dest.to_path_buf();
+        let inner = out.strip_prefix(dest).unwrap_or(&out);
+        for component in inner.parent().into_iter().flat_map(Path::components) {
+            dir.push(component);
+            if std::fs::symlink_metadata(&dir).is_ok_and(|meta| meta.file_type().is_symlink()) {
+                return Err(CliError::Copy(format!(
+                    "refusing to unpack {} through the symlink {}",
+                    path.display(),
+                    dir.display()
+                )));
+            }
+        }
+
+        if let Some(parent) = out.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        entry.unpack(&out)?;
+    }
+    Ok(())
+}
+
+/// Archives the file or directory at `source` under the top level name `name`, ready for
+/// uploading into a container.
+pub fn pack(source: &Path, name: &str) -> Result<Vec<u8>, CliError> {
+    let mut archive = tar::Builder::new(Vec::new());
+    archive.follow_symlinks(false);
+
+    if source.is_dir() {
+        archive.append_dir_all(name, source)?;
+    } else {
+        archive.append_path_with_name(source, name)?;
+    }
+    Ok(archive.into_inner()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    fn file_header(size: usize) -> tar::Header {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(size as u64);
+        header.set_mode(0o644);
+        header
+    }
+
+    #[test]
+    fn unpack_copies_into_existing_directory() {
+        let dest = TempDir::new("unpack-into");
+        let mut builder = tar::Builder::new(Vec::new());
+        builder
+            .append_data(&mut file_header(2), "app/hello.txt", &b"hi"[..])
+            .unwrap();
+        let archive = builder.into_inner().unwrap();
+
+        unpack(&archive, &dest.0).unwrap();
+        let copied = std::fs::read_to_string(dest.0.join("app/hello.txt")).unwrap();
+        assert_eq!(copied, "hi");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unpack_refuses_to_write_through_extracted_symlink() {
+        let dest = TempDir::new("unpack-dest");
+        let outside = TempDir::new("unpack-outside");
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut link = tar::Header::new_gnu();
+        link.set_entry_type(tar::EntryType::Symlink);
+        link.set_size(0);
+        builder.append_link(&mut link, "x", &outside.0).unwrap();
+        builder
+            .append_data(&mut file_header(4), "x/passwd", &b"evil"[..])
+            .unwrap();
+        let archive = builder.into_inner().unwrap();
+
+        assert!(matches!(unpack(&archive, &dest.0), Err(CliError::Copy(_))));
+        assert!(!outside.0.join("passwd").exists());
+    }
+
+    #[test]
+    fn unpack_refuses_parent_components() {
+        let dest = TempDir::new("unpack-parent");
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = file_header(4);
+        // `append_data` rejects `..` itself, so the name is written into the header directly
+        header.as_gnu_mut().unwrap().name[..12].copy_from_slice(b"../escape.tx");
+        header.set_cksum();
+        builder.append(&header, &b"evil"[..]).unwrap();
+        let archive = builder.into_inner().unwrap();
+
+        assert!(matches!(unpack(&archive, &dest.0), Err(CliError::Copy(_))));
+    }
+}
@@ -0,0 +1,84 @@
+//! Creating a container from a spec file kept alongside the code it runs
+//!
+//! The spec is the body of the Engine API's container create call, as JSON or YAML, e.g.
+//!
+//! ```yaml
+//! Image: nginx:1.27
+//! Env: ["NGINX_PORT=8080"]
+//! ExposedPorts: { "8080/tcp": {} }
+//! HostConfig:
+//!   PortBindings: { "8080/tcp": [{ HostPort: "8080" }] }
+//!   RestartPolicy: { Name: unless-stopped }
+//! ```
+
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+use bollard::container::{Config, CreateContainerOptions};
+use bollard::Docker;
+
+use crate::error::CliError;
+use crate::parse_platform;
+
+#[derive(Debug, Args)]
+pub struct Create {
+    /// Spec file with the container's Config and HostConfig, read as JSON when it ends in
+    /// `.json` and as YAML otherwise
+    #[arg(short, long, value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Name of the container, generated by the daemon when omitted
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Platform of the image to create the container from (os/arch[/variant], e.g.
+    /// linux/arm64)
+    #[arg(long, value_parser = parse_platform)]
+    pub platform: Option<String>,
+}
+
+/// Reads and checks the spec at `path`. Parse errors carry the line and column they're at.
+fn load_spec(path: &Path) -> Result<Config<String>, CliError> {
+    let error = |reason: String| CliError::Spec {
+        path: path.display().to_string(),
+        reason,
+    };
+
+    let contents = std::fs::read_to_string(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => error("no such file".to_string()),
+        _ => error(e.to_string()),
+    })?;
+
+    let config: Config<String> = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&contents).map_err(|e| error(e.to_string()))?
+    } else {
+        serde_yaml::from_str(&contents).map_err(|e| error(e.to_string()))?
+    };
+
+    if config.image.as_deref().map_or(true, str::is_empty) {
+        return Err(error("missing the required Image field".to_string()));
+    }
+    Ok(config)
+}
+
+// ./exe create -f <spec> [--name <name>] [--platform <os/arch>]
+pub async fn run(docker: &Docker, create: &Create) -> Result<(), CliError> {
+    let Create {
+        file,
+        name,
+        platform,
+    } = create;
+    let config = load_spec(file)?;
+
+    // An empty name has the daemon generate one, as when no options are sent at all
+    let options = (name.is_some() || platform.is_some()).then(|| CreateContainerOptions {
+        name: name.as_deref().unwrap_or_default(),
+        platform: platform.as_deref(),
+    });
+    let created = docker.create_container(options, config).await?;
+    for warning in created.warnings.iter().filter(|w| !w.is_empty()) {
+        eprintln!("Warning: {warning}");
+    }
+
+    Ok(println!("{}", created.id))
+}
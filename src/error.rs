@@ -0,0 +1,281 @@
+//! Errors surfaced to the user by the CLI
+
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    /// The daemon could not be reached at all
+    #[error("Cannot connect to the Docker daemon at {host}: {source}")]
+    Connection {
+        host: String,
+        #[source]
+        source: bollard::errors::Error,
+    },
+
+    /// The `--config` file is missing or malformed
+    #[error("Cannot load config file {path}: {reason}")]
+    Config { path: String, reason: String },
+
+    /// A `create --file` spec or compose file is missing, malformed or incomplete
+    #[error("Cannot use spec file {path}: {reason}")]
+    Spec { path: String, reason: String },
+
+    /// `--context` or `DOCKER_CONTEXT` names a context that doesn't exist
+    #[error("Context {0:?} does not exist (see `context ls`)")]
+    UnknownContext(String),
+
+    /// The daemon socket is missing or isn't a socket
+    #[error("Cannot use the Docker socket {path}: {reason}")]
+    Socket { path: String, reason: String },
+
+    /// TLS was requested but some of the certificate files don't exist
+    #[error("Missing TLS certificate file(s): {}", .0.join(", "))]
+    MissingCerts(Vec<String>),
+
+    /// A request to the Docker API failed
+    #[error(transparent)]
+    Docker(#[from] bollard::errors::Error),
+
+    /// An error reported by the daemon in the middle of a progress stream
+    #[error("{0}")]
+    Daemon(String),
+
+    /// The registry turned the credentials (or lack of them) down
+    #[error("Authentication with {registry} failed: {message} (run `docker login {registry}` or pass --username and --password)")]
+    Auth { registry: String, message: String },
+
+    /// The build context directory can't be packed up
+    #[error("Cannot prepare the build context: {0}")]
+    BuildContext(String),
+
+    /// `ps cp` was given paths it can't copy between
+    #[error("Cannot copy: {0}")]
+    Copy(String),
+
+    /// A `--format` template refers to something that isn't there
+    #[error("Cannot render the --format template: {0}")]
+    Template(String),
+
+    /// The daemon answered with data the CLI can't make sense of
+    #[error("Unexpected response from the Docker daemon: {0}")]
+    UnexpectedResponse(String),
+
+    /// The daemon refused because of the object's state or a name in use (409), with the
+    /// daemon's message
+    #[error("{0}")]
+    Conflict(String),
+
+    /// An empty container name or ID, which as a prefix would match every container
+    #[error("Container name or ID can't be empty")]
+    EmptyTarget,
+
+    /// No container has the given name, ID or ID prefix
+    #[error("No such container: {0}")]
+    NoSuchContainer(String),
+
+    /// `ps port` was asked for a port the container doesn't publish
+    #[error("No public port {port} published for {container}")]
+    PortNotPublished { port: String, container: String },
+
+    /// An ID prefix matches more than one container
+    #[error("{target:?} matches more than one container: {}", .candidates.join(", "))]
+    AmbiguousContainer {
+        target: String,
+        candidates: Vec<String>,
+    },
+
+    /// `--timeout` ran out before the command finished
+    #[error("Operation timed out after {0}s")]
+    Timeout(u64),
+
+    /// Some of the targets of a multi-target command failed
+    #[error("{failed} {resource}(s) failed to {action}")]
+    Batch {
+        failed: usize,
+        resource: &'static str,
+        action: &'static str,
+        /// The exit code the failures had in common, 1 when they differ
+        code: u8,
+    },
+
+    /// A process inside a container finished with a non-zero status, which the CLI passes on
+    /// as its own exit code without printing anything
+    #[error("process exited with status {0}")]
+    ExitStatus(u8),
+
+    /// A listing run with `--exit-code` came back empty, which exits 1 without printing anything
+    #[error("no matches")]
+    NoMatches,
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl CliError {
+    /// The status the CLI exits with, so scripts can tell an unreachable daemon from a missing
+    /// or busy object without parsing the message.
+    pub fn exit_code(&self) -> u8 {
+        use bollard::errors::Error;
+
+        match self {
+            CliError::ExitStatus(code) => *code,
+            CliError::Connection { source, .. } if io_kind(source) == Some(PERMISSION) => 6,
+            CliError::Connection { .. } | CliError::Socket { .. } | CliError::MissingCerts(_) => 3,
+            CliError::Docker(error) => match error {
+                Error::DockerResponseServerError { status_code, .. } => match status_code {
+                    404 => 4,
+                    409 => 5,
+                    401 | 403 => 6,
+                    _ => 1,
+                },
+                _ if io_kind(error) == Some(PERMISSION) => 6,
+                Error::HyperLegacyError { .. } | Error::IOError { .. } => 3,
+                _ => 1,
+            },
+            CliError::EmptyTarget => 2,
+            CliError::NoSuchContainer(_) => 4,
+            CliError::Conflict(_) => 5,
+            CliError::Batch { code, .. } => *code,
+            CliError::Auth { .. } => 6,
+            _ => 1,
+        }
+    }
+
+    /// A line of advice printed under the error when the likely cause is well known.
+    pub fn hint(&self) -> Option<&'static str> {
+        let source = match self {
+            CliError::Connection { source, .. } | CliError::Docker(source) => source,
+            _ => return None,
+        };
+        match io_kind(source)? {
+            std::io::ErrorKind::ConnectionRefused | std::io::ErrorKind::NotFound => {
+                Some("Is the Docker daemon running?")
+            }
+            PERMISSION => {
+                Some("Does your user have access to the Docker socket (is it in the docker group)?")
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Tallies the targets of a multi-target command that failed, each failure having been
+/// reported as it happened.
+#[derive(Debug, Default)]
+pub struct Failures {
+    failed: usize,
+    code: Option<u8>,
+}
+
+impl Failures {
+    pub fn record(&mut self, error: impl Into<CliError>) {
+        let code = error.into().exit_code();
+        self.code = match self.code {
+            Some(common) if common != code => Some(1),
+            _ => Some(code),
+        };
+        self.failed += 1;
+    }
+
+    /// `Ok` when nothing failed, otherwise a [`CliError::Batch`] exiting with the code the
+    /// failures share.
+    pub fn finish(self, resource: &'static str, action: &'static str) -> Result<(), CliError> {
+        match self.code {
+            None => Ok(()),
+            Some(code) => Err(CliError::Batch {
+                failed: self.failed,
+                resource,
+                action,
+                code,
+            }),
+        }
+    }
+}
+
+const PERMISSION: std::io::ErrorKind = std::io::ErrorKind::PermissionDenied;
+
+/// The kind of the I/O error somewhere down `error`'s source chain, which is where hyper leaves
+/// a refused or forbidden socket connection.
+fn io_kind(error: &bollard::errors::Error) -> Option<std::io::ErrorKind> {
+    // Transparent, so its source is the I/O error's own source rather than the I/O error
+    if let bollard::errors::Error::IOError { err } = error {
+        return Some(err.kind());
+    }
+    let mut next: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(error) = next {
+        if let Some(io) = error.downcast_ref::<std::io::Error>() {
+            return Some(io.kind());
+        }
+        next = error.source();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daemon_error(status_code: u16) -> CliError {
+        CliError::Docker(bollard::errors::Error::DockerResponseServerError {
+            status_code,
+            message: "boom".to_string(),
+        })
+    }
+
+    #[test]
+    fn exit_code_follows_the_status_code() {
+        assert_eq!(daemon_error(404).exit_code(), 4);
+        assert_eq!(daemon_error(409).exit_code(), 5);
+        assert_eq!(daemon_error(401).exit_code(), 6);
+        assert_eq!(daemon_error(403).exit_code(), 6);
+        assert_eq!(daemon_error(500).exit_code(), 1);
+    }
+
+    #[test]
+    fn exit_code_of_cli_errors() {
+        let refused = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+        let connection = CliError::Connection {
+            host: "unix:///var/run/docker.sock".to_string(),
+            source: refused.into(),
+        };
+        assert_eq!(connection.exit_code(), 3);
+        assert_eq!(connection.hint(), Some("Is the Docker daemon running?"));
+
+        let denied = std::io::Error::from(PERMISSION);
+        let forbidden = CliError::Connection {
+            host: "unix:///var/run/docker.sock".to_string(),
+            source: denied.into(),
+        };
+        assert_eq!(forbidden.exit_code(), 6);
+
+        assert_eq!(CliError::NoSuchContainer("web".to_string()).exit_code(), 4);
+        assert_eq!(CliError::Conflict("name in use".to_string()).exit_code(), 5);
+        assert_eq!(CliError::ExitStatus(42).exit_code(), 42);
+        assert_eq!(CliError::Timeout(5).exit_code(), 1);
+        assert_eq!(daemon_error(404).hint(), None);
+    }
+
+    #[test]
+    fn failures_exit_with_the_code_they_share() {
+        assert!(Failures::default().finish("container", "stop").is_ok());
+
+        let mut conflicts = Failures::default();
+        conflicts.record(CliError::Conflict("already paused".to_string()));
+        conflicts.record(daemon_error(409));
+        let error = conflicts.finish("container", "pause").unwrap_err();
+        assert_eq!(error.exit_code(), 5);
+        assert_eq!(error.to_string(), "2 container(s) failed to pause");
+
+        let mut mixed = Failures::default();
+        mixed.record(daemon_error(404));
+        mixed.record(daemon_error(409));
+        assert_eq!(
+            mixed.finish("container", "pause").unwrap_err().exit_code(),
+            1
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Streaming daemon events
+
+use clap::Args;
+
+use bollard::models::{EventActor, EventMessage};
+use bollard::system::EventsOptions;
+use bollard::Docker;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::CliError;
+use crate::{
+    filter_map, parse_filter, parse_timestamp, print_record, until_interrupted, OutputArgs,
+};
+
+#[derive(Debug, Args)]
+pub struct Events {
+    /// Filter events by key=value (e.g. type=container, event=start), may be repeated
+    #[arg(long = "filter", value_name = "KEY=VALUE", value_parser = parse_filter)]
+    pub filters: Vec<(String, String)>,
+
+    /// Show events created since this time (RFC3339, Unix timestamp, or a duration ago like 10m)
+    #[arg(long, value_parser = parse_timestamp)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Stop streaming at this time (RFC3339, Unix timestamp, or a duration ago like 10m)
+    #[arg(long, value_parser = parse_timestamp)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+// ./exe events [--filter key=value]... [--since <time>] [--until <time>]
+pub async fn run(docker: &Docker, events: &Events, output: &OutputArgs) -> Result<(), CliError> {
+    let Events {
+        filters,
+        since,
+        until,
+    } = events;
+
+    let options = EventsOptions {
+        since: *since,
+        until: *until,
+        filters: filter_map(filters),
+    };
+
+    let event_stream = docker.events(Some(options));
+    until_interrupted(event_stream, |event| {
+        // A stream has no end to close an array at, so JSON of either shape is one event per line
+        if output.is_json() {
+            return print_record(output, &event);
+        }
+        Ok(println!("{}", event_line(&event)))
+    })
+    .await?;
+    Ok(())
+}
+
+/// Renders an event the way `docker events` does: time, type, action, actor ID and the actor's
+/// attributes sorted by key.
+fn event_line(event: &EventMessage) -> String {
+    let EventMessage {
+        typ,
+        action,
+        actor,
+        time,
+        time_nano,
+        ..
+    } = event;
+
+    let timestamp = time_nano
+        .map(DateTime::from_timestamp_nanos)
+        .or_else(|| time.and_then(|secs| DateTime::from_timestamp(secs, 0)))
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_default();
+
+    let (id, attributes) = match actor {
+        Some(EventActor { id, attributes }) => {
+            let mut attributes: Vec<_> = attributes
+                .iter()
+                .flatten()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect();
+            attributes.sort();
+            (id.as_deref().unwrap_or(""), attributes.join(", "))
+        }
+        None => ("", String::new()),
+    };
+
+    format!(
+        "{timestamp} {} {} {id} ({attributes})",
+        typ.map(|typ| typ.to_string()).unwrap_or_default(),
+        action.as_deref().unwrap_or(""),
+    )
+}
@@ -2,46 +2,391 @@
 #![allow(clippy::all)]
 #![allow(unused)]
 
-use clap::{Args, Parser, Subcommand};
-use std::collections::HashMap;
+mod attach;
+mod build_context;
+mod completions;
+mod compose;
+mod config;
+mod connection;
+mod context;
+mod copy;
+mod create;
+mod error;
+mod events;
+mod network;
+mod progress;
+mod registry;
+mod run;
+mod system;
+mod template;
+#[cfg(test)]
+mod testing;
+mod volume;
+
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::default::Default;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
 use futures_util::stream;
-use futures_util::stream::StreamExt;
+use futures_util::stream::{StreamExt, TryStreamExt};
 
-use bollard::container::{InspectContainerOptions, KillContainerOptions, ListContainersOptions};
-use bollard::image::ListImagesOptions;
-use bollard::models::ContainerSummary;
+use bollard::container::{
+    Config as ContainerConfig, DownloadFromContainerOptions, InspectContainerOptions,
+    KillContainerOptions, ListContainersOptions, LogOutput, LogsOptions, MemoryStats,
+    MemoryStatsStats, RemoveContainerOptions, RenameContainerOptions, RestartContainerOptions,
+    StartContainerOptions, Stats, StatsOptions, StopContainerOptions, TopOptions,
+    UpdateContainerOptions, UploadToContainerOptions, WaitContainerOptions,
+};
+use bollard::exec::{CreateExecOptions, StartExecResults};
+use bollard::image::{
+    BuildImageOptions, CommitContainerOptions, CreateImageOptions, ImportImageOptions,
+    ListImagesOptions, PruneImagesOptions, PushImageOptions, RemoveImageOptions,
+    SearchImagesOptions, TagImageOptions,
+};
+use bollard::models::{
+    BuildInfo, BuildInfoAux, ChangeType, ContainerSummary, ContainerTopResponse, CreateImageInfo,
+    FilesystemChange, HistoryResponseItem, ImageDeleteResponseItem, ImageId, ImageInspect,
+    ImageInspectRootFs, ImageSearchResponseItem, MountPoint, Port, PortBinding, PortMap,
+    PushImageInfo, RestartPolicy, RestartPolicyNameEnum,
+};
 use bollard::secret::{ContainerInspectResponse, ImageSummary};
 use bollard::Docker;
 
 use prettytable::{row, Cell, Row, Table};
 
+use chrono::{DateTime, Utc};
+
+use regex::Regex;
+
+use tracing::level_filters::LevelFilter;
+
+use crate::completions::Completions;
+use crate::compose::Compose;
+use crate::config::Config;
+use crate::connection::{ConnectOpts, Retry};
+use crate::context::Context;
+use crate::copy::{parse_copy_path, CopyPath};
+use crate::create::Create;
+use crate::error::{CliError, Failures};
+use crate::events::Events;
+use crate::network::Network;
+use crate::progress::Progress;
+use crate::run::Run;
+use crate::system::System;
+use crate::volume::Volume;
+
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    /// Optional name to operate on
-    name: Option<String>,
-
-    /// Sets a custom config file
+    /// Config file (TOML, or JSON with a .json extension) with defaults for the host, TLS,
+    /// format and filters; command line flags override it, and it overrides the environment
     #[arg(short, long, value_name = "FILE")]
     config: Option<PathBuf>,
 
-    /// Turn debugging information on
+    /// Log what the CLI is doing to stderr: -d for info, -dd for debug (API requests), -ddd
+    /// for trace
     #[arg(short, long, action = clap::ArgAction::Count)]
     debug: u8,
 
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    connection: ConnectOpts,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(try_from = "String")]
+enum OutputFormat {
+    /// Human readable table
+    Table,
+    /// The table with every useful column and nothing shortened, as if `--no-trunc`,
+    /// `ps info --mounts --command` and `img list --digests` had all been given
+    Wide,
+    /// The raw API response serialized as JSON
+    Json,
+    /// RFC 4180 CSV with a header row, for `img list` and `ps info`
+    Csv,
+    /// JSON Lines: one object per line, written as soon as it's available, so listings and
+    /// the `events` and `stats` streams can be processed incrementally
+    Jsonl,
+    /// Fields picked out of the JSON with `{{.Path}}` placeholders, see [`template`]
+    Template(String),
+    /// A handlebars template applied to each item's JSON, given as `tmpl:...`
+    Handlebars(String),
+}
+
+impl TryFrom<String> for OutputFormat {
+    type Error = String;
+
+    fn try_from(format: String) -> Result<OutputFormat, String> {
+        parse_output_format(&format)
+    }
+}
+
+/// Parses `--format`: one of the fixed formats, a handlebars template after `tmpl:`, or
+/// anything else with a `{{` in it as a Go-style template.
+fn parse_output_format(format: &str) -> Result<OutputFormat, String> {
+    if let Some(template) = format.strip_prefix("tmpl:") {
+        return template::check_handlebars(template).map(OutputFormat::Handlebars);
+    }
+
+    match format {
+        "table" => Ok(OutputFormat::Table),
+        "wide" => Ok(OutputFormat::Wide),
+        "json" => Ok(OutputFormat::Json),
+        "csv" => Ok(OutputFormat::Csv),
+        "jsonl" => Ok(OutputFormat::Jsonl),
+        template if template.contains("{{") => Ok(OutputFormat::Template(template.to_string())),
+        _ => Err(format!(
+            "expected table, wide, json, csv, jsonl or a template like '{{{{.Id}}}}', got {format:?}"
+        )),
+    }
+}
+
+// How results are presented, shared by every command. A plain comment for the same reason as
+// on `ConnectOpts`.
+#[derive(Debug, Args)]
+struct OutputArgs {
+    /// Output format: table, wide, json, csv, jsonl, or a template like '{{.State.Status}}'
+    ///
+    /// table is for humans, and wide is the same with every useful column and nothing shortened
+    /// (full IDs and tags, mounts, commands, digests); json is the raw API response; csv has a
+    /// header row, for `img list` and `ps info`; jsonl is one JSON object per line, written as each
+    /// is available, so listings and the `events` and `stats` streams can be processed
+    /// incrementally. A template is filled in from the JSON, once per item for listings, with
+    /// `{{.Path}}` placeholders like `docker inspect --format` (array elements by index, as in
+    /// `{{.Names.0}}`). Prefixed with `tmpl:` it is a handlebars template instead, e.g.
+    /// 'tmpl:{{Names.[0]}} {{State}}'.
+    #[arg(short = 'o', long, value_name = "FORMAT", default_value = "table", value_parser = parse_output_format, global = true)]
+    format: OutputFormat,
+
+    /// Print sizes as raw byte counts instead of human-readable units
+    #[arg(long, global = true)]
+    bytes: bool,
+
+    /// Print full IDs, image references and commands instead of shortening them
+    #[arg(long, global = true)]
+    no_trunc: bool,
+
+    /// Print timestamps as absolute RFC3339 times instead of "3 hours ago"
+    #[arg(long, global = true)]
+    no_relative: bool,
+
+    /// Leave the header row out of tables (and CSV)
+    #[arg(long, global = true)]
+    no_headers: bool,
+
+    /// Print `--format json` on a single line instead of indented, for piping
+    #[arg(long, global = true)]
+    compact: bool,
+
+    /// Fit listing tables into this many columns by cutting long cells short, defaults to the
+    /// terminal's width (and no limit when piped or with --no-trunc)
+    #[arg(long, global = true, value_name = "COLUMNS")]
+    max_width: Option<usize>,
+
+    /// Write what would go to stdout to this file instead, creating missing parent
+    /// directories. Streams like `ps logs -f` are written to it as they arrive
+    #[arg(long, global = true, value_name = "PATH")]
+    output_file: Option<PathBuf>,
+}
+
+impl OutputArgs {
+    /// Whether the raw API response was asked for, as JSON of either shape or through a
+    /// template.
+    fn is_json(&self) -> bool {
+        matches!(
+            self.format,
+            OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Template(_)
+                | OutputFormat::Handlebars(_)
+        )
+    }
+
+    /// Whether the output is a table for humans, `--format wide` included.
+    fn is_table(&self) -> bool {
+        matches!(self.format, OutputFormat::Table | OutputFormat::Wide)
+    }
+
+    /// Serializes a `--format json` response, indented unless `--compact` was given.
+    fn json<T: serde::Serialize + ?Sized>(&self, value: &T) -> serde_json::Result<String> {
+        if self.compact {
+            serde_json::to_string(value)
+        } else {
+            serde_json::to_string_pretty(value)
+        }
+    }
+
+    /// Renders a size in bytes, human-readable unless `--bytes` was given.
+    fn size(&self, bytes: i64) -> String {
+        if self.bytes {
+            bytes.to_string()
+        } else {
+            human_bytes(bytes)
+        }
+    }
+
+    /// Shortens an ID to 12 characters unless `--no-trunc` was given.
+    fn id<'a>(&self, id: &'a str) -> &'a str {
+        if self.no_trunc {
+            id
+        } else {
+            short_id(id)
+        }
+    }
+
+    /// Shortens an image reference the way `docker ps` does unless `--no-trunc` was given: bare
+    /// image IDs become short IDs and pinned digests are dropped.
+    fn image<'a>(&self, image: &'a str) -> &'a str {
+        if self.no_trunc {
+            image
+        } else if image.starts_with("sha256:") {
+            short_id(image)
+        } else {
+            image.split_once('@').map_or(image, |(repo, _)| repo)
+        }
+    }
+
+    /// Cuts text down to `max` characters unless `--no-trunc` was given.
+    fn text(&self, text: &str, max: usize) -> String {
+        if self.no_trunc {
+            text.to_string()
+        } else {
+            truncate(text, max)
+        }
+    }
+
+    /// A table starting with the `header` row, unless `--no-headers` was given.
+    fn table(&self, header: Row) -> Table {
+        let mut table = Table::new();
+        if !self.no_headers {
+            table.add_row(header);
+        }
+        table
+    }
+
+    /// How wide listing tables may get, `None` meaning as wide as they need.
+    fn max_width(&self) -> Option<usize> {
+        if self.max_width.is_some() || self.no_trunc {
+            return self.max_width;
+        }
+        terminal_size::terminal_size().map(|(width, _)| usize::from(width.0))
+    }
+
+    /// Renders a Unix timestamp, relative to now unless `--no-relative` was given.
+    fn time(&self, secs: i64) -> String {
+        match DateTime::from_timestamp(secs, 0) {
+            Some(time) if self.no_relative => time.to_rfc3339(),
+            Some(time) => format!("{} ago", human_duration(Utc::now() - time)),
+            None => String::from("-"),
+        }
+    }
+}
+
+impl Cli {
+    /// Parses the command line and fills in whatever it left out from the `--config` file.
+    fn from_matches(matches: &ArgMatches) -> Result<Cli, CliError> {
+        let mut cli = Cli::from_arg_matches(matches).unwrap_or_else(|e| e.exit());
+        if let Some(path) = cli.config.as_deref() {
+            let config = Config::load(path)?;
+            cli.apply_config(config, matches)?;
+        }
+        let on_command_line = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        cli.connection.context_over_host = on_command_line("context") && !on_command_line("host");
+        if cli.output.format == OutputFormat::Wide {
+            cli.output.no_trunc = true;
+        }
+        Ok(cli)
+    }
+
+    /// Overrides environment variables and built-in defaults with the config file, leaving
+    /// anything given on the command line alone.
+    fn apply_config(&mut self, config: Config, matches: &ArgMatches) -> Result<(), CliError> {
+        let on_command_line = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        let connection = &mut self.connection;
+        if !on_command_line("host") && config.host.is_some() {
+            connection.host = config.host;
+        }
+        if let Some(tlsverify) = config.tlsverify.filter(|_| !on_command_line("tlsverify")) {
+            connection.tlsverify = tlsverify;
+        }
+        if !on_command_line("cert_path") && config.cert_path.is_some() {
+            connection.cert_path = config.cert_path;
+        }
+        connection.tlscert = connection.tlscert.take().or(config.tlscert);
+        connection.tlskey = connection.tlskey.take().or(config.tlskey);
+        connection.tlscacert = connection.tlscacert.take().or(config.tlscacert);
+        if let Some(format) = config.format.filter(|_| !on_command_line("format")) {
+            self.output.format = format;
+        }
+
+        if let Some(Commands::Ps(Ps {
+            command: PsOptions::Info(ListContainersInfo { filters, .. }),
+        })) = &mut self.command
+        {
+            if filters.is_empty() {
+                *filters = config.filters;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Subcommand)]
 enum Commands {
     /// Working with Docker Images
     Img(Img),
     /// Show Docker Processes
     Ps(Ps),
+    /// Create And Start A Container From An Image
+    Run(Run),
+    /// Create A Container From A JSON Or YAML Spec File
+    Create(Create),
+    /// Bring The Services Of A Compose File Up Or Down
+    Compose(Compose),
+    /// Create A New Image From A Container's Changes
+    Commit(CommitInfo),
+    /// Manage Docker Volumes
+    Volume(Volume),
+    /// Manage Docker Networks
+    Network(Network),
+    /// Daemon-Wide Information And Housekeeping
+    System(System),
+    /// Stream Real-Time Events From The Daemon
+    Events(Events),
+    /// Manage Docker CLI Contexts
+    Context(Context),
+    /// Print A Shell Completion Script To Stdout
+    #[command(hide = true)]
+    Completions(Completions),
+}
+
+#[derive(Debug, Args)]
+pub struct CommitInfo {
+    /// Container Name, ID or unique ID prefix of the Docker Container
+    pub container_name: String,
+
+    /// Reference for the new image (e.g. app:debug), the tag defaults to `latest`
+    pub image: String,
+
+    /// Commit message recorded in the image history
+    #[arg(short, long)]
+    pub message: Option<String>,
+
+    /// Author of the image (e.g. "Jane Doe <jane@example.com>")
+    #[arg(short, long)]
+    pub author: Option<String>,
 }
 
 #[derive(Debug, Args)]
@@ -52,8 +397,226 @@ pub struct Img {
 
 #[derive(Debug, Subcommand)]
 pub enum ImgOptions {
-    /// List All OCI Images
-    List,
+    /// List OCI Images
+    List(ListImagesInfo),
+    /// Pull An Image From A Registry
+    Pull(PullImageInfo),
+    /// Push An Image To A Registry
+    Push(PushInfo),
+    /// Remove One Or More Images
+    Rm(RemoveImageInfo),
+    /// Show Detailed Information On An Image
+    Inspect(InspectImageInfo),
+    /// Remove Dangling (Or All Unused) Images
+    Prune(PruneImagesInfo),
+    /// Show The Layer History Of An Image
+    History(ImageHistoryInfo),
+    /// Create A Tag That Refers To An Existing Image
+    Tag(TagImageInfo),
+    /// Tag Every Image Matching A Pattern Under A New Prefix
+    Retag(RetagImagesInfo),
+    /// Build An Image From A Dockerfile
+    Build(BuildImageInfo),
+    /// Save One Or More Images To A Tar Archive
+    Save(SaveImagesInfo),
+    /// Load Images From A Tar Archive
+    Load(LoadImagesInfo),
+    /// Search Docker Hub For Images
+    Search(SearchImagesInfo),
+    /// Create An Image From A Root Filesystem Tarball
+    Import(ImportImageInfo),
+}
+
+#[derive(Debug, Args)]
+pub struct ListImagesInfo {
+    /// Only print full image IDs
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Include intermediate images, which are left out by default like `docker images`
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Only show dangling (untagged) images, same as `--filter dangling=true`
+    #[arg(long)]
+    pub dangling: bool,
+
+    /// Order the listing by this field
+    #[arg(long, value_enum, default_value_t = ImageSort::Size)]
+    pub sort: ImageSort,
+
+    /// Filter output by key=value (e.g. dangling=true, reference=nginx*, label=foo), may be
+    /// repeated
+    #[arg(long = "filter", value_name = "KEY=VALUE", value_parser = parse_filter)]
+    pub filters: Vec<(String, String)>,
+
+    /// Add a column with each image's repo digest
+    #[arg(long)]
+    pub digests: bool,
+
+    /// Exit with status 1 when nothing matches, so scripts can check for that without parsing
+    /// the output
+    #[arg(long)]
+    pub exit_code: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImageSort {
+    /// Largest first
+    Size,
+    /// Newest first
+    Created,
+    /// Alphabetically by repo tag
+    Repo,
+}
+
+#[derive(Debug, Args)]
+pub struct PullImageInfo {
+    /// Image Reference (e.g. nginx:latest), the tag defaults to `latest`
+    pub image: String,
+
+    /// Pull the image for this platform instead of the daemon's own (os/arch[/variant], e.g.
+    /// linux/arm64)
+    #[arg(long, value_parser = parse_platform)]
+    pub platform: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct PushInfo {
+    /// Image Reference (e.g. registry.example.com/app:1.0), the tag defaults to `latest`
+    pub image: String,
+
+    /// Registry username, instead of the login saved in ~/.docker/config.json
+    #[arg(long)]
+    pub username: Option<String>,
+
+    /// Registry password or access token
+    #[arg(long, requires = "username")]
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct RemoveImageInfo {
+    /// Image IDs or Tags of the Images to remove
+    #[arg(required = true)]
+    pub images: Vec<String>,
+
+    /// Remove the image even if it is being used by a container
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Do not delete untagged parent images
+    #[arg(long)]
+    pub no_prune: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct InspectImageInfo {
+    /// Image ID or Tag of the Image to inspect
+    pub image: String,
+
+    /// Print the raw inspect response as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct PruneImagesInfo {
+    /// Remove all unused images, not just dangling ones
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Do not prompt for confirmation
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ImageHistoryInfo {
+    /// Image ID or Tag of the Image
+    pub image: String,
+}
+
+#[derive(Debug, Args)]
+pub struct SearchImagesInfo {
+    /// Term to search for (e.g. postgres)
+    pub term: String,
+
+    /// Return at most this many results
+    #[arg(long)]
+    pub limit: Option<u64>,
+
+    /// Filter results by key=value (stars=3, is-official=true, is-automated=false), may be
+    /// repeated
+    #[arg(long = "filter", value_name = "KEY=VALUE", value_parser = parse_filter)]
+    pub filters: Vec<(String, String)>,
+}
+
+#[derive(Debug, Args)]
+pub struct TagImageInfo {
+    /// Image ID or Tag of the existing Image
+    pub source: String,
+
+    /// New reference (e.g. registry.example.com/app:1.0), the tag defaults to `latest`
+    pub target: String,
+}
+
+#[derive(Debug, Args)]
+pub struct RetagImagesInfo {
+    /// Pattern of the references to retag (e.g. myapp/*), `*` not crossing a `/`
+    #[arg(value_parser = parse_reference_pattern)]
+    pub pattern: glob::Pattern,
+
+    /// Where to put them (e.g. registry.example.com/myapp/*), the `*` standing for whatever
+    /// followed the pattern's literal prefix
+    #[arg(value_parser = parse_retag_target)]
+    pub target: String,
+}
+
+#[derive(Debug, Args)]
+pub struct BuildImageInfo {
+    /// Directory to use as the build context
+    #[arg(default_value = ".")]
+    pub context: PathBuf,
+
+    /// Name and optionally tag the image (e.g. app:1.0)
+    #[arg(short, long)]
+    pub tag: Option<String>,
+
+    /// Path of the Dockerfile, relative to the context
+    #[arg(short, long, default_value = "Dockerfile")]
+    pub file: String,
+}
+
+#[derive(Debug, Args)]
+pub struct SaveImagesInfo {
+    /// Image IDs or Tags of the Images to save
+    #[arg(required = true)]
+    pub images: Vec<String>,
+
+    /// Write the archive to this file instead of stdout (`-o` is taken by `--format`)
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct LoadImagesInfo {
+    /// Read the archive from this file instead of stdin. It is read into memory whole before
+    /// being sent, so multi-GB archives need as much free memory
+    #[arg(short, long, value_name = "FILE")]
+    pub input: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ImportImageInfo {
+    /// Tarball of a root filesystem: a file, `-` for stdin, or an http(s) URL the daemon
+    /// downloads itself. A file or stdin is read into memory whole before being sent, so
+    /// multi-GB tarballs need as much free memory
+    pub source: String,
+
+    /// Reference for the new image (e.g. rootfs:1.0), the tag defaults to `latest`
+    #[arg(short, long, value_name = "IMAGE")]
+    pub tag: Option<String>,
 }
 
 #[derive(Debug, Args)]
@@ -64,196 +627,3695 @@ pub struct Ps {
 
 #[derive(Debug, Subcommand)]
 pub enum PsOptions {
-    /// All Running Containers
-    Info,
+    /// List Containers, Only Running Ones Unless --all Is Given
+    Info(ListContainersInfo),
     /// Kill A Running Containers Process
-    Kill(ContainerInfo),
+    Kill(KillContainerInfo),
+    /// Gracefully Stop A Running Container
+    Stop(ContainerInfo),
+    /// Start One Or More Stopped Containers
+    Start(ContainerNames),
+    /// Restart A Container
+    Restart(ContainerInfo),
+    /// Block Until A Container Stops, Then Print Its Exit Code
+    Wait(WaitInfo),
+    /// Pause All Processes In One Or More Containers
+    Pause(ContainerNames),
+    /// Unpause All Processes In One Or More Containers
+    Unpause(ContainerNames),
+    /// Rename A Container
+    Rename(RenameContainerInfo),
+    /// Change The Resource Limits Of A Container Without Recreating It
+    Update(UpdateContainerInfo),
+    /// Remove One Or More Containers
+    Rm(RemoveContainerInfo),
+    /// Fetch The Logs Of A Container
+    Logs(LogsInfo),
+    /// Show Detailed Information On A Container
+    Inspect(InspectContainerInfo),
+    /// Run A Command In A Running Container
+    Exec(ExecInfo),
+    /// Attach The Terminal To A Running Container
+    Attach(AttachInfo),
+    /// Live Resource Usage Of A Container
+    Stats(StatsInfo),
+    /// Show The Running Processes Of A Container
+    Top(TopInfo),
+    /// Copy Files Between A Container And The Local Filesystem
+    Cp(CopyInfo),
+    /// Show Files Added, Changed Or Deleted In A Container's Filesystem
+    Diff(DiffInfo),
+    /// Export A Container's Filesystem As A Tar Archive
+    Export(ExportContainerInfo),
+    /// List The Published Port Mappings Of A Container
+    Port(PortInfo),
+}
+
+#[derive(Debug, Args)]
+#[command(group(clap::ArgGroup::new("inspecting").args(["inspect", "long"]).multiple(true)))]
+pub struct ListContainersInfo {
+    /// Only print full container IDs
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Show all containers, not just running ones
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Filter output by key=value (e.g. name=web, status=exited, label=app=api,
+    /// health=unhealthy, ancestor=nginx:latest), may be repeated
+    #[arg(long = "filter", value_name = "KEY=VALUE", value_parser = parse_filter)]
+    pub filters: Vec<(String, String)>,
+
+    /// Only show containers created from this image or one built on it, same as
+    /// `--filter ancestor=<image>`
+    #[arg(long, value_name = "IMAGE")]
+    pub image: Option<String>,
+
+    /// Order the listing by this field
+    #[arg(long, value_enum, default_value_t = ContainerSort::Name)]
+    pub sort: ContainerSort,
+
+    /// Show only the N most recently created containers, stopped ones included
+    #[arg(short = 'n', long, value_name = "N")]
+    pub last: Option<usize>,
+
+    /// Show only the most recently created container, same as `-n 1`
+    #[arg(short, long, conflicts_with = "last")]
+    pub latest: bool,
+
+    /// Show only containers created after this one (name, ID or ID prefix), stopped ones
+    /// included
+    #[arg(long, value_name = "CONTAINER")]
+    pub since: Option<String>,
+
+    /// Show only containers created before this one (name, ID or ID prefix), stopped ones
+    /// included
+    #[arg(long, value_name = "CONTAINER")]
+    pub before: Option<String>,
+
+    /// Keep refreshing the listing until interrupted with Ctrl-C
+    #[arg(short, long)]
+    pub watch: bool,
+
+    /// Seconds between refreshes in watch mode
+    #[arg(long, value_name = "SECONDS", default_value_t = 2, requires = "watch")]
+    pub interval: u64,
+
+    /// Add Size and Virtual columns with the size of each container's writable layer, and of
+    /// that layer together with its image, which the daemon is slow to work out
+    #[arg(short, long)]
+    pub size: bool,
+
+    /// Add a column listing each container's volumes and bind mounts
+    #[arg(long)]
+    pub mounts: bool,
+
+    /// Add a column with the command each container runs, cut to 20 characters unless
+    /// --no-trunc
+    #[arg(long)]
+    pub command: bool,
+
+    /// Add a column with the value of this label, may be repeated
+    #[arg(long = "label", value_name = "KEY")]
+    pub labels: Vec<String>,
+
+    /// Inspect every listed container and print the detailed table for each
+    #[arg(long)]
+    pub inspect: bool,
+
+    /// Add Exit and Restarts columns with each container's last exit code and restart count,
+    /// which takes an inspect per container
+    #[arg(long)]
+    pub long: bool,
+
+    /// How many containers to inspect at once with --inspect or --long
+    #[arg(long, value_name = "N", default_value_t = 4, requires = "inspecting", value_parser = clap::value_parser!(u16).range(1..))]
+    pub concurrency: u16,
+
+    /// Exit with status 1 when nothing matches, so scripts can check for that without parsing
+    /// the output
+    #[arg(long, conflicts_with = "watch")]
+    pub exit_code: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ContainerSort {
+    /// Alphabetically by container name
+    Name,
+    /// Newest first
+    Created,
+    /// Alphabetically by state, then by name
+    State,
 }
 
 #[derive(Debug, Args)]
 pub struct ContainerInfo {
-    /// Container Name of the Docker Container
+    /// Container Names, IDs or unique ID prefixes of the Docker Containers, `-` reading them
+    /// from stdin one per line
+    #[arg(required = true)]
+    pub container_names: Vec<String>,
+
+    /// Seconds to wait for the container to stop before killing it
+    #[arg(short, long, default_value_t = 10)]
+    pub time: i64,
+}
+
+#[derive(Debug, Args)]
+pub struct KillContainerInfo {
+    /// Container Names, IDs or unique ID prefixes of the Docker Containers, `-` reading them
+    /// from stdin one per line
+    #[arg(required = true)]
+    pub container_names: Vec<String>,
+
+    /// Signal to send to the container (name like SIGHUP, or a number)
+    #[arg(short, long, default_value = "SIGKILL", value_parser = parse_signal)]
+    pub signal: String,
+}
+
+#[derive(Debug, Args)]
+pub struct ContainerNames {
+    /// Container Names, IDs or unique ID prefixes of the Docker Containers, `-` reading them
+    /// from stdin one per line
+    #[arg(required = true)]
+    pub container_names: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct WaitInfo {
+    /// Container Name, ID or unique ID prefix of the Docker Container
     pub container_name: String,
+
+    /// What to wait for
+    #[arg(long, value_enum, default_value_t = WaitCondition::NotRunning)]
+    pub condition: WaitCondition,
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
-    let cli = Cli::parse();
-    let docker = Docker::connect_with_socket_defaults().unwrap();
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WaitCondition {
+    /// Return as soon as the container isn't running, straight away if it already stopped
+    NotRunning,
+    /// Wait for the container's next exit, even if it isn't running yet
+    NextExit,
+    /// Wait until the container has been removed
+    Removed,
+}
 
-    // You can check the value provided by positional arguments, or option arguments
-    if let Some(name) = cli.name.as_deref() {
-        println!("Value for name: {name}");
-    }
+#[derive(Debug, Args)]
+pub struct RenameContainerInfo {
+    /// Container Name, ID or unique ID prefix of the Docker Container
+    pub container_name: String,
 
-    if let Some(config_path) = cli.config.as_deref() {
-        println!("Value for config: {}", config_path.display());
-    }
+    /// New name for the container
+    #[arg(value_parser = parse_container_name)]
+    pub new_name: String,
+}
 
-    // You can see how many times a particular flag or argument occurred
-    // Note, only flags can have multiple occurrences
-    match cli.debug {
-        0 => println!("Debug mode is off"),
-        1 => println!("Debug mode is kind of on"),
-        2 => println!("Debug mode is on"),
-        _ => println!("Don't be crazy"),
-    }
+#[derive(Debug, Args)]
+#[command(group(clap::ArgGroup::new("limits").required(true).multiple(true)))]
+pub struct UpdateContainerInfo {
+    /// Container Name, ID or unique ID prefix of the Docker Container
+    pub container_name: String,
 
-    // You can check for the existence of subcommands, and if found use their
-    // matches just as you would the top level cmd
-    match &cli.command {
-        Some(Commands::Img(Img { command })) => match command {
-            // ./exe img list
-            ImgOptions::List => {
-                let images = &docker
-                    .list_images(Some(ListImagesOptions::<String> {
-                        all: true,
-                        ..Default::default()
-                    }))
-                    .await
-                    .unwrap();
+    /// Memory limit (e.g. 512m, 2g)
+    #[arg(short, long, value_name = "SIZE", group = "limits", value_parser = parse_memory)]
+    pub memory: Option<i64>,
 
-                // Container Summary table
-                let mut image_summary_table = Table::new();
-                image_summary_table.add_row(row![b->"ID", b->"Image Tag", b->"Size(KB)"]);
+    /// Number of CPUs the container may use (e.g. 1.5)
+    #[arg(long, value_name = "N", group = "limits")]
+    pub cpus: Option<f64>,
 
-                for ImageSummary { id, size, repo_tags, .. } in images.iter() {
-                    let image_summary_row = Row::new(vec![
-                        Cell::new(&id.strip_prefix("sha256:").unwrap()[..12]),
-                        Cell::new(repo_tags.iter().next().unwrap()),
-                        Cell::new(&(size / (1024 as i64)).to_string()),
-                    ]);
+    /// Relative CPU weight against other containers
+    #[arg(long, value_name = "N", group = "limits")]
+    pub cpu_shares: Option<isize>,
 
-                    image_summary_table.add_row(image_summary_row);
-                }
+    /// CPUs the container may run on (e.g. 0-3 or 0,1)
+    #[arg(long, value_name = "CPUS", group = "limits")]
+    pub cpuset_cpus: Option<String>,
 
-                image_summary_table.printstd();
+    /// Restart policy: no, always, unless-stopped or on-failure[:max-retries]
+    #[arg(long, value_name = "POLICY", group = "limits", value_parser = parse_restart_policy)]
+    pub restart: Option<RestartPolicy>,
+}
 
-                // for image in images {
-                //     let ImageSummary { id, .. } = &image;
-                //     // println!("[->] {:?}", image);
-                //     println!("[->] Container ID {:?}", id);
-                // }
-                Ok(())
-            }
-        },
-        Some(Commands::Ps(Ps { command })) => match command {
-            // ./exe ps info
-            PsOptions::Info => {
-                let mut list_container_filters = HashMap::new();
-                list_container_filters.insert("status", vec!["running"]);
-
-                let containers = &docker
-                    .list_containers(Some(ListContainersOptions {
-                        all: true,
-                        filters: list_container_filters,
-                        ..Default::default()
-                    }))
-                    .await?;
+#[derive(Debug, Args)]
+pub struct RemoveContainerInfo {
+    /// Container Names, IDs or unique ID prefixes of the Docker Containers, `-` reading them
+    /// from stdin one per line
+    #[arg(required = true)]
+    pub container_names: Vec<String>,
 
-                // let docker_stream = stream::repeat(docker);
-                // docker_stream
-                //     .zip(stream::iter(containers))
-                //     .for_each_concurrent(2, conc)
-                //     .await;
-                // println!("[#] Running container {:?}", containers);
+    /// Kill the container first if it is running
+    #[arg(short, long)]
+    pub force: bool,
 
-                // Container Summary table
-                let mut container_summary_table = Table::new();
-                container_summary_table
-                    .add_row(row![b->"ID", b->"Container Name", b->"Image", b->"State"]);
+    /// Remove anonymous volumes associated with the container
+    #[arg(short, long)]
+    pub volumes: bool,
+}
 
-                for ContainerSummary {
-                    id,
-                    names,
-                    image,
-                    state,
-                    ..
-                } in containers.iter()
-                {
-                    let container_summary_row = Row::new(vec![
-                        Cell::new(&id.as_deref().unwrap_or("")[..12]),
-                        Cell::new(
-                            &names
-                                .as_ref()
-                                .map_or_else(|| "n/a".to_string(), |vec| vec.join(", "))
-                                .strip_prefix("/")
-                                .unwrap_or_else(|| "n/a"),
-                        ),
-                        Cell::new(image.as_deref().unwrap_or("")),
-                        Cell::new(state.as_deref().unwrap_or("")),
-                    ]);
+#[derive(Debug, Args)]
+pub struct InspectContainerInfo {
+    /// Container Name, ID or unique ID prefix of the Docker Container
+    pub container_name: String,
 
-                    container_summary_table.add_row(container_summary_row);
-                }
+    /// Print the raw inspect response as JSON
+    #[arg(long)]
+    pub json: bool,
 
-                // Print the table to stdout
-                container_summary_table.printstd();
+    /// Print the container's environment variables instead, one KEY=VALUE per line
+    #[arg(long)]
+    pub env: bool,
 
-                Ok(println!("All Running Docker Containers Info"))
-            }
-            // ./exe ps kill <container_name>
-            PsOptions::Kill(opt) => {
-                let options = KillContainerOptions { signal: "SIGTERM" };
-                match opt {
-                    ContainerInfo { container_name } => {
-                        let _ = &docker.kill_container(container_name, Some(options));
-                        Ok(println!("Kills Container ID: {container_name:?}"))
-                    }
-                }
-            }
-        },
-        None => Ok(()),
-    }
+    /// Only print the environment variables matching this regex (implies --env)
+    #[arg(long, value_name = "REGEX", value_parser = parse_regex)]
+    pub env_grep: Option<Regex>,
 }
 
-async fn conc(arg: (Docker, &ContainerSummary)) {
-    let (docker, container) = arg;
+#[derive(Debug, Args)]
+pub struct ExecInfo {
+    /// Container Name, ID or unique ID prefix of the Docker Container
+    pub container_name: String,
 
-    let stats = docker
-        .inspect_container(
-            container.id.as_ref().unwrap(),
-            None::<InspectContainerOptions>,
-        )
-        .await
-        .unwrap();
-    let ContainerInspectResponse {
-        id,
-        name,
-        image,
-        size_root_fs,
-        state,
-        ..
-    } = stats;
+    /// Command to run, passed after `--` (e.g. -- sh -c "ls /")
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    pub command: Vec<String>,
+}
 
-    // println!("[#] Container name  {:?}", name);
+#[derive(Debug, Args)]
+pub struct AttachInfo {
+    /// Container Name, ID or unique ID prefix of the Docker Container
+    pub container_name: String,
 
-    // Create the table
-    let mut stats_table = Table::new();
-    stats_table.add_row(
-        row![b->"ID", b->"Container Name", b->"Image ID", b->"Container Size", b->"State",],
-    );
+    /// Key sequence that detaches and leaves the container running (e.g. ctrl-a,d)
+    #[arg(long, default_value = attach::DEFAULT_DETACH_KEYS)]
+    pub detach_keys: String,
 
-    let stats_row = Row::new(vec![
-        Cell::new(id.as_deref().unwrap_or("")),
-        Cell::new(name.as_deref().unwrap_or("")),
-        Cell::new(image.as_deref().unwrap_or("")),
-        Cell::new(
-            &size_root_fs
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| String::from("-")),
-        ),
-        Cell::new(
-            state
-                .unwrap()
-                .status
-                .as_ref()
-                .map(|st| st.as_ref())
-                .unwrap(),
-        ),
-    ]);
-    stats_table.add_row(stats_row);
+    /// Only show the container's output, without forwarding stdin
+    #[arg(long)]
+    pub no_stdin: bool,
+}
 
-    // Print the table to stdout
-    stats_table.printstd();
+#[derive(Debug, Args)]
+pub struct StatsInfo {
+    /// Container Names, IDs or unique ID prefixes, defaults to every running container
+    pub container_names: Vec<String>,
+
+    /// Print a single snapshot and exit
+    #[arg(long)]
+    pub no_stream: bool,
+
+    /// Columns to show, comma separated (e.g. cpu,mem,net), defaults to all of them
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "COLUMNS")]
+    pub columns: Vec<StatsColumn>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatsColumn {
+    /// CPU %
+    Cpu,
+    /// Memory usage, limit and %
+    Mem,
+    /// Network bytes received and sent
+    Net,
+    /// Block device bytes read and written
+    Block,
+    /// Number of processes
+    Pids,
+}
+
+#[derive(Debug, Args)]
+pub struct TopInfo {
+    /// Container Name, ID or unique ID prefix of the Docker Container
+    pub container_name: String,
+
+    /// Arguments passed to `ps` inside the container (e.g. aux), defaults to `-ef`
+    #[arg(long, value_name = "ARGS", allow_hyphen_values = true)]
+    pub ps_args: Option<String>,
+}
+
+#[derive(Debug, Args)]
+#[command(after_help = "\
+Examples:
+  locci ps cp web:/etc/nginx/nginx.conf ./nginx.conf
+  locci ps cp ./site web:/usr/share/nginx/html")]
+pub struct CopyInfo {
+    /// Where to copy from, `container:/path` or a local path
+    #[arg(value_name = "SRC", value_parser = parse_copy_path)]
+    pub source: CopyPath,
+
+    /// Where to copy to, `container:/path` or a local path
+    #[arg(value_name = "DEST", value_parser = parse_copy_path)]
+    pub destination: CopyPath,
+}
+
+#[derive(Debug, Args)]
+pub struct PortInfo {
+    /// Container Name, ID or unique ID prefix of the Docker Container
+    pub container_name: String,
+
+    /// Only print the host bindings of this container port, the protocol defaulting to tcp
+    #[arg(value_name = "PORT[/PROTO]", value_parser = parse_container_port)]
+    pub port: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct DiffInfo {
+    /// Container Name, ID or unique ID prefix of the Docker Container
+    pub container_name: String,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportContainerInfo {
+    /// Container Name, ID or unique ID prefix of the Docker Container
+    pub container_name: String,
+
+    /// Write the archive to this file instead of stdout (`-o` is taken by `--format`)
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct LogsInfo {
+    /// Container Names, IDs or unique ID prefixes of the Docker Containers, several (up to 16)
+    /// being interleaved with each line prefixed by its container's name
+    #[arg(required = true, num_args = 1..=MAX_LOG_STREAMS)]
+    pub container_names: Vec<String>,
+
+    /// Keep streaming new log output
+    #[arg(short, long)]
+    pub follow: bool,
+
+    /// Number of lines to show from the end of the logs
+    #[arg(long, value_name = "N")]
+    pub tail: Option<u64>,
+
+    /// Show timestamps
+    #[arg(short, long)]
+    pub timestamps: bool,
+
+    /// Only show logs since this time (RFC3339, Unix timestamp, or a duration ago like 10m)
+    #[arg(long, value_parser = parse_timestamp)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only show logs before this time (RFC3339, Unix timestamp, or a duration ago like 1h)
+    #[arg(long, value_parser = parse_timestamp)]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Only show lines matching this regular expression
+    #[arg(long, value_name = "PATTERN", value_parser = parse_regex)]
+    pub grep: Option<Regex>,
+
+    /// Lines of context to show around each match, like `grep -C`
+    #[arg(
+        short = 'C',
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        requires = "grep"
+    )]
+    pub context: usize,
+
+    /// Start each line with the labels and environment variables the container's log driver
+    /// was told to attach (its `labels`, `env`, `labels-regex` and `env-regex` log options)
+    #[arg(long)]
+    pub details: bool,
+
+    /// Start each line with the stream it came from, or the container's name with
+    /// `--prefix=name`
+    #[arg(
+        long,
+        value_enum,
+        value_name = "KIND",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "stream"
+    )]
+    pub prefix: Option<LogPrefix>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogPrefix {
+    /// `[stdout]` or `[stderr]` (`[console]` for containers with a TTY, which merges the two)
+    Stream,
+    /// `[name]` of the container
+    Name,
+}
+
+#[tokio::main]
+/// Exits with
+///
+/// * `0` when every operation succeeded,
+/// * `1` when any other daemon operation failed, or when `--exit-code` was given and nothing
+///   matched,
+/// * `2` on usage errors such as bad flags or a missing subcommand,
+/// * `3` when the daemon can't be reached (refused connection, missing socket or certificates),
+/// * `4` when the container, image or other object doesn't exist (404),
+/// * `5` on conflicts such as a name in use or a container in the wrong state (409),
+/// * `6` when permission is denied, by the socket, the daemon (401/403) or a registry,
+/// * the process's own status for `ps exec`, `ps wait`, `ps attach` and foreground `run`.
+///
+/// Multi-target commands that fail for some targets exit with the code those failures share,
+/// or `1` when they failed in different ways.
+async fn main() -> ExitCode {
+    let matches = Cli::command().get_matches();
+    let result = match Cli::from_matches(&matches) {
+        Ok(cli) => run(cli).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::ExitStatus(code)) => ExitCode::from(code),
+        Err(CliError::NoMatches) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            if let Some(hint) = e.hint() {
+                eprintln!("{hint}");
+            }
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), CliError> {
+    init_logging(cli.debug);
+    if let Some(path) = &cli.output.output_file {
+        redirect_stdout(path)?;
+    }
+
+    match &cli.command {
+        // Generating completions only needs the parser, not a daemon
+        Some(Commands::Completions(options)) => return Ok(completions::run(options)),
+        // Contexts are read from disk, so no daemon is needed either
+        Some(Commands::Context(Context { command })) => {
+            return context::run(command, cli.connection.context.as_deref(), &cli.output);
+        }
+        // Nothing to do is a usage error, same as clap's own
+        None => {
+            eprint!("{}", Cli::command().render_help());
+            return Err(CliError::ExitStatus(2));
+        }
+        Some(_) => {}
+    }
+
+    let docker = connection::connect(&cli.connection)?;
+    connection::ping(&docker, &cli.connection).await?;
+
+    match cli.connection.timeout {
+        Some(secs) => tokio::time::timeout(Duration::from_secs(secs), execute(&cli, &docker))
+            .await
+            .unwrap_or(Err(CliError::Timeout(secs))),
+        None => execute(&cli, &docker).await,
+    }
+}
+
+/// Carries out the subcommand against a connected daemon.
+async fn execute(cli: &Cli, docker: &Docker) -> Result<(), CliError> {
+    let retry = cli.connection.retry();
+
+    // You can check for the existence of subcommands, and if found use their
+    // matches just as you would the top level cmd
+    match &cli.command {
+        Some(Commands::Img(Img { command })) => match command {
+            // ./exe img list [-q] [-a] [--dangling] [--filter key=value] [--exit-code]
+            ImgOptions::List(ListImagesInfo {
+                quiet,
+                all,
+                dangling,
+                sort,
+                filters,
+                digests,
+                exit_code,
+            }) => {
+                let mut list_image_filters = filter_map(filters);
+                if *dangling {
+                    list_image_filters.insert("dangling".to_string(), vec!["true".to_string()]);
+                }
+
+                let options = ListImagesOptions::<String> {
+                    all: *all,
+                    filters: list_image_filters,
+                    ..Default::default()
+                };
+                let mut images = retry
+                    .run("list images", || docker.list_images(Some(options.clone())))
+                    .await?;
+                sort_images(&mut images, *sort);
+                let images = &images;
+                let found = match *exit_code && images.is_empty() {
+                    true => Err(CliError::NoMatches),
+                    false => Ok(()),
+                };
+
+                if *quiet {
+                    images.iter().for_each(|image| println!("{}", image.id));
+                    return found;
+                }
+
+                if cli.output.is_json() {
+                    print_json_list(&cli.output, images)?;
+                    return found;
+                }
+
+                if images.is_empty() && cli.output.is_table() {
+                    println!("No images found.");
+                    return found;
+                }
+
+                // Image Summary listing
+                let digests = *digests || cli.output.format == OutputFormat::Wide;
+                let image_summary_rows = image_rows(images, &cli.output, digests);
+
+                let mut headers = vec!["ID", "Image Tag"];
+                if digests {
+                    headers.push("Digest");
+                }
+                headers.extend(["Created", "Size"]);
+                print_listing(&cli.output, &headers, image_summary_rows)?;
+
+                // for image in images {
+                //     let ImageSummary { id, .. } = &image;
+                //     // println!("[->] {:?}", image);
+                //     println!("[->] Container ID {:?}", id);
+                // }
+                found
+            }
+            // ./exe img pull [--platform <os/arch>] <image[:tag]>
+            ImgOptions::Pull(PullImageInfo { image, platform }) => {
+                let (from_image, tag) = split_image_ref(image);
+                let options = CreateImageOptions {
+                    from_image,
+                    tag,
+                    platform: platform.as_deref().unwrap_or_default(),
+                    ..Default::default()
+                };
+
+                let mut bars = Progress::new();
+                let mut progress_stream = docker.create_image(Some(options), None, None);
+                while let Some(info) = progress_stream.next().await {
+                    let CreateImageInfo {
+                        id,
+                        status,
+                        progress,
+                        progress_detail,
+                        error,
+                        ..
+                    } = info?;
+
+                    if let Some(error) = error {
+                        return Err(CliError::Daemon(error));
+                    }
+
+                    bars.update(
+                        id.as_deref(),
+                        status.as_deref().unwrap_or_default(),
+                        progress.as_deref(),
+                        progress_detail.as_ref(),
+                    );
+                }
+                drop(bars);
+
+                Ok(println!("Pulled Image: {from_image}:{tag}"))
+            }
+            // ./exe img push [--username <user> --password <password>] <image[:tag]>
+            ImgOptions::Push(PushInfo {
+                image,
+                username,
+                password,
+            }) => {
+                let (name, tag) = split_image_ref(image);
+                let registry = registry::registry_host(name);
+                let credentials =
+                    registry::credentials(registry, username.as_deref(), password.as_deref())?;
+
+                let options = PushImageOptions { tag };
+                let mut bars = Progress::new();
+                let mut progress_stream = docker.push_image(name, Some(options), credentials);
+                while let Some(info) = progress_stream.next().await {
+                    let PushImageInfo {
+                        status,
+                        progress,
+                        progress_detail,
+                        error,
+                    } = info?;
+
+                    if let Some(error) = error {
+                        // Registries word this differently, but always with one of these
+                        if error.contains("unauthorized")
+                            || error.contains("denied")
+                            || error.contains("authentication required")
+                        {
+                            return Err(CliError::Auth {
+                                registry: registry.to_string(),
+                                message: error,
+                            });
+                        }
+                        return Err(CliError::Daemon(error));
+                    }
+
+                    // The push progress bollard hands on doesn't say which layer it's about
+                    bars.update(
+                        None,
+                        status.as_deref().unwrap_or_default(),
+                        progress.as_deref(),
+                        progress_detail.as_ref(),
+                    );
+                }
+                drop(bars);
+
+                Ok(println!("Pushed Image: {name}:{tag}"))
+            }
+            // ./exe img rm [-f] [--no-prune] <image>...
+            ImgOptions::Rm(RemoveImageInfo {
+                images,
+                force,
+                no_prune,
+            }) => {
+                let options = RemoveImageOptions {
+                    force: *force,
+                    noprune: *no_prune,
+                };
+
+                let mut failed = Failures::default();
+                for image in images {
+                    match docker.remove_image(image, Some(options), None).await {
+                        Ok(removed) => {
+                            for ImageDeleteResponseItem { untagged, deleted } in removed {
+                                if let Some(untagged) = untagged {
+                                    println!("Untagged: {untagged}");
+                                }
+                                if let Some(deleted) = deleted {
+                                    println!("Deleted: {deleted}");
+                                }
+                            }
+                        }
+                        // The image is referenced by a container
+                        Err(bollard::errors::Error::DockerResponseServerError {
+                            status_code: 409,
+                            message,
+                        }) => {
+                            eprintln!("Failed to remove image {image:?}: {message} (use --force to remove it anyway)");
+                            failed.record(CliError::Conflict(message));
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to remove image {image:?}: {e}");
+                            failed.record(e);
+                        }
+                    }
+                }
+
+                failed.finish("image", "be removed")?;
+                Ok(())
+            }
+            // ./exe img inspect [--json] <image>
+            ImgOptions::Inspect(InspectImageInfo { image, json }) => {
+                let inspect = retry
+                    .run("inspect image", || docker.inspect_image(image))
+                    .await?;
+
+                if *json || cli.output.is_json() {
+                    return print_json(&cli.output, &inspect);
+                }
+
+                let ImageInspect {
+                    id,
+                    repo_tags,
+                    created,
+                    size,
+                    architecture,
+                    os,
+                    root_fs,
+                    ..
+                } = inspect;
+
+                // Image Inspect table
+                let mut image_inspect_table = cli.output.table(
+                    row![b->"ID", b->"Image Tags", b->"Created", b->"Size", b->"Architecture", b->"OS", b->"Layers"],
+                );
+
+                let image_inspect_row = Row::new(vec![
+                    Cell::new(id.as_deref().unwrap_or("")),
+                    Cell::new(
+                        &repo_tags
+                            .filter(|tags| !tags.is_empty())
+                            .map_or_else(|| "<none>".to_string(), |tags| tags.join(", ")),
+                    ),
+                    Cell::new(created.as_deref().unwrap_or("")),
+                    Cell::new(
+                        &size
+                            .map(|s| cli.output.size(s))
+                            .unwrap_or_else(|| String::from("-")),
+                    ),
+                    Cell::new(architecture.as_deref().unwrap_or("")),
+                    Cell::new(os.as_deref().unwrap_or("")),
+                    Cell::new(
+                        &root_fs
+                            .and_then(|ImageInspectRootFs { layers, .. }| layers)
+                            .map_or(0, |layers| layers.len())
+                            .to_string(),
+                    ),
+                ]);
+                image_inspect_table.add_row(image_inspect_row);
+
+                image_inspect_table.printstd();
+                Ok(())
+            }
+            // ./exe img prune [-a] [-f]
+            ImgOptions::Prune(PruneImagesInfo { all, force }) => {
+                let warning = if *all {
+                    "This will remove all images without at least one container associated to them."
+                } else {
+                    "This will remove all dangling images."
+                };
+                if !*force && !confirm(warning)? {
+                    return Ok(println!("Prune cancelled"));
+                }
+
+                let mut filters = HashMap::new();
+                if *all {
+                    filters.insert("dangling", vec!["false"]);
+                }
+
+                let pruned = docker
+                    .prune_images(Some(PruneImagesOptions { filters }))
+                    .await?;
+                for ImageDeleteResponseItem { untagged, deleted } in
+                    pruned.images_deleted.iter().flatten()
+                {
+                    if let Some(untagged) = untagged {
+                        println!("Untagged: {untagged}");
+                    }
+                    if let Some(deleted) = deleted {
+                        println!("Deleted: {deleted}");
+                    }
+                }
+
+                Ok(println!(
+                    "Total reclaimed space: {}",
+                    cli.output.size(pruned.space_reclaimed.unwrap_or(0))
+                ))
+            }
+            // ./exe img history [--no-trunc] <image>
+            ImgOptions::History(ImageHistoryInfo { image }) => {
+                let history = docker.image_history(image).await?;
+
+                if cli.output.is_json() {
+                    return print_json_list(&cli.output, &history);
+                }
+
+                // Image History table
+                let mut image_history_table = cli.output.table(
+                    row![b->"Image", b->"Created", b->"Created By", b->"Size", b->"Comment"],
+                );
+
+                for HistoryResponseItem {
+                    id,
+                    created,
+                    created_by,
+                    size,
+                    comment,
+                    ..
+                } in history.iter()
+                {
+                    // Layers built elsewhere have no local image ID
+                    let id = match id.as_str() {
+                        "<missing>" | "" => "<missing>",
+                        id => cli.output.id(id),
+                    };
+                    let created_by = cli.output.text(created_by, 60);
+
+                    image_history_table.add_row(Row::new(vec![
+                        Cell::new(id),
+                        Cell::new(&cli.output.time(*created)),
+                        Cell::new(&created_by),
+                        Cell::new(&cli.output.size(*size)),
+                        Cell::new(comment),
+                    ]));
+                }
+
+                image_history_table.printstd();
+                Ok(())
+            }
+            // ./exe img build [-t <tag>] [-f <dockerfile>] [context]
+            ImgOptions::Build(BuildImageInfo { context, tag, file }) => {
+                if !context.is_dir() {
+                    return Err(CliError::BuildContext(format!(
+                        "{} is not a directory",
+                        context.display()
+                    )));
+                }
+                let tarball = build_context::tar_context(context, file)?;
+
+                let options = BuildImageOptions {
+                    dockerfile: build_context::dockerfile_path(context, file),
+                    t: tag.clone().unwrap_or_default(),
+                    rm: true,
+                    ..Default::default()
+                };
+
+                let mut image_id = None;
+                let mut bars = Progress::new();
+                let mut build_stream = docker.build_image(options, None, Some(tarball.into()));
+                while let Some(info) = build_stream.next().await {
+                    let BuildInfo {
+                        id,
+                        stream,
+                        error,
+                        status,
+                        progress,
+                        progress_detail,
+                        aux,
+                        ..
+                    } = info?;
+
+                    if let Some(error) = error {
+                        return Err(CliError::Daemon(error));
+                    }
+                    if let Some(stream) = stream {
+                        bars.println(&stream);
+                    }
+                    // Pulls of the base image report their progress here
+                    if let Some(status) = status {
+                        bars.update(
+                            id.as_deref(),
+                            &status,
+                            progress.as_deref(),
+                            progress_detail.as_ref(),
+                        );
+                    }
+                    if let Some(BuildInfoAux::Default(ImageId { id: Some(id) })) = aux {
+                        image_id = Some(id);
+                    }
+                }
+                drop(bars);
+
+                match image_id {
+                    Some(id) => Ok(println!("Built Image: {id}")),
+                    None => Err(CliError::UnexpectedResponse(
+                        "the build finished without reporting an image ID".to_string(),
+                    )),
+                }
+            }
+            // ./exe img save [--output <file>] <image>...
+            ImgOptions::Save(SaveImagesInfo { images, output }) => {
+                let mut archive: Box<dyn Write> = match output {
+                    Some(path) => Box::new(std::fs::File::create(path)?),
+                    // A tarball is no use on a terminal and can leave it in a mess
+                    None if std::io::IsTerminal::is_terminal(&std::io::stdout()) => {
+                        return Err(CliError::Io(std::io::Error::other(
+                            "refusing to write an image archive to a terminal, use --output or redirect stdout",
+                        )));
+                    }
+                    None => Box::new(std::io::stdout().lock()),
+                };
+
+                // Written chunk by chunk so large images never sit in memory whole
+                let names: Vec<&str> = images.iter().map(String::as_str).collect();
+                let mut tar_stream = docker.export_images(&names);
+                while let Some(chunk) = tar_stream.next().await {
+                    archive.write_all(&chunk?)?;
+                }
+                archive.flush()?;
+
+                if let Some(path) = output {
+                    eprintln!("Saved {} Image(s) to {}", images.len(), path.display());
+                }
+                Ok(())
+            }
+            // ./exe img load [-i <file>]
+            ImgOptions::Load(LoadImagesInfo { input }) => {
+                let archive = read_archive(input.as_deref())?;
+
+                let options = ImportImageOptions { quiet: false };
+                let mut load_stream = docker.import_image(options, archive.into(), None);
+                while let Some(info) = load_stream.next().await {
+                    let BuildInfo { stream, error, .. } = info?;
+
+                    if let Some(error) = error {
+                        return Err(CliError::Daemon(error));
+                    }
+                    if let Some(stream) = stream {
+                        print!("{stream}");
+                    }
+                }
+                Ok(())
+            }
+            // ./exe img import [-t <image[:tag]>] <file|-|url>
+            ImgOptions::Import(ImportImageInfo { source, tag }) => {
+                let remote = source.starts_with("http://") || source.starts_with("https://");
+                let root_fs = match source.as_str() {
+                    _ if remote => None,
+                    "-" => Some(read_archive(None)?.into()),
+                    path => Some(read_archive(Some(Path::new(path)))?.into()),
+                };
+
+                // `-` tells the daemon the tarball is in the request body
+                let (repo, tag) = tag.as_deref().map_or(("", ""), split_image_ref);
+                let options = CreateImageOptions {
+                    from_src: if remote { source.as_str() } else { "-" },
+                    repo,
+                    tag,
+                    ..Default::default()
+                };
+
+                // The last status the daemon sends is the new image's ID
+                let mut image_id = None;
+                let mut import_stream = docker.create_image(Some(options), root_fs, None);
+                while let Some(info) = import_stream.next().await {
+                    let CreateImageInfo { status, error, .. } = info?;
+
+                    if let Some(error) = error {
+                        return Err(CliError::Daemon(error));
+                    }
+                    image_id = status.or(image_id);
+                }
+
+                let image_id = image_id.ok_or_else(|| {
+                    CliError::UnexpectedResponse("no image ID in the import response".to_string())
+                })?;
+                Ok(println!("{image_id}"))
+            }
+            // ./exe img tag <source> <target[:tag]>
+            ImgOptions::Tag(TagImageInfo { source, target }) => {
+                let (repo, tag) = split_image_ref(target);
+                let options = TagImageOptions { repo, tag };
+
+                docker.tag_image(source, Some(options)).await?;
+                Ok(println!("Tagged Image: {source} as {repo}:{tag}"))
+            }
+            // ./exe img retag <pattern> <prefix/*>
+            ImgOptions::Retag(RetagImagesInfo { pattern, target }) => {
+                let images = retry
+                    .run("list images", || {
+                        docker.list_images(None::<ListImagesOptions<String>>)
+                    })
+                    .await?;
+                let existing: HashSet<&str> = images
+                    .iter()
+                    .flat_map(|image| image.repo_tags.iter().map(String::as_str))
+                    .collect();
+
+                // The part of the pattern before its first wildcard is what gets swapped out
+                let literal = pattern
+                    .as_str()
+                    .find(['*', '?', '['])
+                    .map_or(pattern.as_str(), |end| &pattern.as_str()[..end]);
+                let match_options = glob::MatchOptions {
+                    require_literal_separator: true,
+                    ..Default::default()
+                };
+
+                let mut matched = 0;
+                let mut failed = Failures::default();
+                for image in &images {
+                    for repo_tag in &image.repo_tags {
+                        let (repo, _) = split_image_ref(repo_tag);
+                        if !pattern.matches_with(repo, match_options)
+                            && !pattern.matches_with(repo_tag, match_options)
+                        {
+                            continue;
+                        }
+                        matched += 1;
+
+                        let retagged = format!(
+                            "{target}{}",
+                            repo_tag.strip_prefix(literal).unwrap_or(repo_tag)
+                        );
+                        if existing.contains(retagged.as_str()) {
+                            println!("Skipped Image: {repo_tag}, {retagged} already exists");
+                            continue;
+                        }
+
+                        let (repo, tag) = split_image_ref(&retagged);
+                        let options = TagImageOptions { repo, tag };
+                        match docker.tag_image(&image.id, Some(options)).await {
+                            Ok(()) => println!("Tagged Image: {repo_tag} as {retagged}"),
+                            Err(e) => {
+                                eprintln!("Failed to tag image {repo_tag} as {retagged}: {e}");
+                                failed.record(e);
+                            }
+                        }
+                    }
+                }
+
+                if matched == 0 {
+                    eprintln!("No images match {:?}", pattern.as_str());
+                }
+                failed.finish("image", "tag")?;
+                Ok(())
+            }
+            // ./exe img search [--limit N] [--filter key=value]... <term>
+            ImgOptions::Search(SearchImagesInfo {
+                term,
+                limit,
+                filters,
+            }) => {
+                let options = SearchImagesOptions {
+                    term: term.clone(),
+                    limit: *limit,
+                    filters: filter_map(filters),
+                };
+                let results = retry
+                    .run("search images", || docker.search_images(options.clone()))
+                    .await?;
+
+                if cli.output.is_json() {
+                    return print_json_list(&cli.output, &results);
+                }
+
+                // Search Results listing
+                let flag = |set: Option<bool>| if set == Some(true) { "[OK]" } else { "" };
+                let search_rows = results
+                    .iter()
+                    .map(
+                        |ImageSearchResponseItem {
+                             name,
+                             description,
+                             star_count,
+                             is_official,
+                             is_automated,
+                         }| {
+                            vec![
+                                Cell::new(name.as_deref().unwrap_or_default()),
+                                Cell::new(
+                                    &cli.output
+                                        .text(description.as_deref().unwrap_or_default(), 45),
+                                ),
+                                Cell::new(&star_count.unwrap_or_default().to_string()),
+                                Cell::new(flag(*is_official)),
+                                Cell::new(flag(*is_automated)),
+                            ]
+                        },
+                    )
+                    .collect();
+
+                print_listing(
+                    &cli.output,
+                    &["Name", "Description", "Stars", "Official", "Automated"],
+                    search_rows,
+                )
+            }
+        },
+        Some(Commands::Ps(Ps { command })) => match command {
+            // ./exe ps info [-q] [-a] [--filter key=value]... [--image <image>] [-n N | -l] [--since <container>] [--before <container>] [-s] [--long] [--command] [--exit-code] [-w [--interval <seconds>]]
+            PsOptions::Info(info) => {
+                if !info.watch {
+                    return print_containers(&docker, retry, info, &cli.output).await;
+                }
+
+                // Redraw until interrupted, with the cursor hidden so the redraws don't flicker
+                terminal_control("\x1B[?25l");
+                let interval = std::time::Duration::from_secs(info.interval);
+                let watched = loop {
+                    let refresh = async {
+                        terminal_control("\x1B[2J\x1B[H");
+                        print_containers(&docker, retry, info, &cli.output).await?;
+                        tokio::time::sleep(interval).await;
+                        Ok::<_, CliError>(())
+                    };
+
+                    tokio::select! {
+                        _ = tokio::signal::ctrl_c() => break Ok(()),
+                        refreshed = refresh => {
+                            if let Err(e) = refreshed {
+                                break Err(e);
+                            }
+                        }
+                    }
+                };
+
+                // Give the terminal its cursor back however the loop ended
+                terminal_control("\x1B[?25h\n");
+                watched
+            }
+            // ./exe ps kill <container_name|->... [-s <signal>]
+            PsOptions::Kill(KillContainerInfo {
+                container_names,
+                signal,
+            }) => {
+                let options = KillContainerOptions {
+                    signal: signal.as_str(),
+                };
+
+                let containers = ContainerResolver::new(&docker, retry).await?;
+                let mut failed = Failures::default();
+                for container_name in &read_targets(container_names)? {
+                    let killed = async {
+                        let id = containers.resolve(container_name)?;
+                        docker.kill_container(&id, Some(options.clone())).await?;
+
+                        // Report what the daemon says happened rather than assuming the signal took
+                        let state = docker
+                            .inspect_container(&id, None::<InspectContainerOptions>)
+                            .await?
+                            .state
+                            .and_then(|state| state.status)
+                            .map_or_else(|| "unknown".to_string(), |status| status.to_string());
+                        Ok::<_, CliError>(state)
+                    };
+                    match killed.await {
+                        Ok(state) => println!(
+                            "Killed Container: {container_name:?} ({signal}, state: {state})"
+                        ),
+                        Err(e) => {
+                            eprintln!("Failed to kill container {container_name:?}: {e}");
+                            failed.record(e);
+                        }
+                    }
+                }
+
+                failed.finish("container", "be killed")?;
+                Ok(())
+            }
+            // ./exe ps stop <container_name|->... [-t <seconds>]
+            PsOptions::Stop(ContainerInfo {
+                container_names,
+                time,
+            }) => {
+                let options = StopContainerOptions { t: *time };
+
+                let containers = ContainerResolver::new(&docker, retry).await?;
+                let mut failed = Failures::default();
+                for container_name in &read_targets(container_names)? {
+                    let stopped = match containers.resolve(container_name) {
+                        Ok(id) => docker
+                            .stop_container(&id, Some(options))
+                            .await
+                            .map_err(CliError::from),
+                        Err(e) => Err(e),
+                    };
+                    match stopped {
+                        Ok(()) => println!("Stopped Container: {container_name:?}"),
+                        Err(e) => {
+                            eprintln!("Failed to stop container {container_name:?}: {e}");
+                            failed.record(e);
+                        }
+                    }
+                }
+
+                failed.finish("container", "stop")?;
+                Ok(())
+            }
+            // ./exe ps start <container_name|->...
+            PsOptions::Start(ContainerNames { container_names }) => {
+                let containers = ContainerResolver::new(&docker, retry).await?;
+                let mut failed = Failures::default();
+                for container_name in &read_targets(container_names)? {
+                    let started = match containers.resolve(container_name) {
+                        Ok(id) => start(&docker, &id).await.map_err(CliError::from),
+                        Err(e) => Err(e),
+                    };
+                    match started {
+                        Ok(true) => println!("Started Container: {container_name:?}"),
+                        Ok(false) => println!("Container {container_name:?} is already running"),
+                        Err(e) => {
+                            eprintln!("Failed to start container {container_name:?}: {e}");
+                            failed.record(e);
+                        }
+                    }
+                }
+
+                failed.finish("container", "start")?;
+                Ok(())
+            }
+            // ./exe ps restart <container_name|short_id|->... [-t <seconds>]
+            PsOptions::Restart(ContainerInfo {
+                container_names,
+                time,
+            }) => {
+                let options = RestartContainerOptions { t: *time as isize };
+
+                let containers = ContainerResolver::new(&docker, retry).await?;
+                let mut failed = Failures::default();
+                for container_name in &read_targets(container_names)? {
+                    let restarted = async {
+                        // Resolve the name or short ID to the full container ID up front
+                        let id = containers.resolve(container_name)?;
+                        docker.restart_container(&id, Some(options)).await?;
+
+                        let state = docker
+                            .inspect_container(&id, None::<InspectContainerOptions>)
+                            .await?
+                            .state
+                            .and_then(|state| state.status)
+                            .map_or_else(|| "unknown".to_string(), |status| status.to_string());
+                        Ok::<_, CliError>(state)
+                    };
+                    match restarted.await {
+                        Ok(state) => {
+                            println!("Restarted Container: {container_name:?} (state: {state})")
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to restart container {container_name:?}: {e}");
+                            failed.record(e);
+                        }
+                    }
+                }
+
+                failed.finish("container", "restart")?;
+                Ok(())
+            }
+            // ./exe ps wait [--condition <condition>] <container_name>
+            PsOptions::Wait(WaitInfo {
+                container_name,
+                condition,
+            }) => {
+                let id = resolve_container(&docker, retry, container_name).await?;
+                let condition = match condition {
+                    WaitCondition::NotRunning => "not-running",
+                    WaitCondition::NextExit => "next-exit",
+                    WaitCondition::Removed => "removed",
+                };
+
+                let mut wait_stream =
+                    docker.wait_container(&id, Some(WaitContainerOptions { condition }));
+                let mut code = 0;
+                while let Some(exit) = wait_stream.next().await {
+                    // Non-zero exits come back as an error carrying the code
+                    code = match exit {
+                        Ok(response) => response.status_code,
+                        Err(bollard::errors::Error::DockerContainerWaitError { code, .. }) => code,
+                        Err(e) => return Err(e.into()),
+                    };
+                }
+
+                println!("{code}");
+                match code {
+                    0 => Ok(()),
+                    code => Err(CliError::ExitStatus(u8::try_from(code).unwrap_or(1))),
+                }
+            }
+            // ./exe ps pause <container_name|->...
+            PsOptions::Pause(ContainerNames { container_names }) => {
+                let containers = ContainerResolver::new(&docker, retry).await?;
+                let mut failed = Failures::default();
+                for container_name in &read_targets(container_names)? {
+                    let paused = match containers.resolve(container_name) {
+                        Ok(id) => docker.pause_container(&id).await.map_err(CliError::from),
+                        Err(e) => Err(e),
+                    };
+                    match paused {
+                        Ok(()) => println!("Paused Container: {container_name:?}"),
+                        // Already paused or not running
+                        Err(CliError::Docker(
+                            bollard::errors::Error::DockerResponseServerError {
+                                status_code: 409,
+                                message,
+                            },
+                        )) => {
+                            eprintln!("Failed to pause container {container_name:?}: {message}");
+                            failed.record(CliError::Conflict(message));
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to pause container {container_name:?}: {e}");
+                            failed.record(e);
+                        }
+                    }
+                }
+
+                failed.finish("container", "pause")?;
+                Ok(())
+            }
+            // ./exe ps unpause <container_name|->...
+            PsOptions::Unpause(ContainerNames { container_names }) => {
+                let containers = ContainerResolver::new(&docker, retry).await?;
+                let mut failed = Failures::default();
+                for container_name in &read_targets(container_names)? {
+                    let unpaused = match containers.resolve(container_name) {
+                        Ok(id) => docker.unpause_container(&id).await.map_err(CliError::from),
+                        Err(e) => Err(e),
+                    };
+                    match unpaused {
+                        Ok(()) => println!("Unpaused Container: {container_name:?}"),
+                        // Not paused in the first place
+                        Err(CliError::Docker(
+                            bollard::errors::Error::DockerResponseServerError {
+                                status_code: 409,
+                                message,
+                            },
+                        )) => {
+                            eprintln!("Failed to unpause container {container_name:?}: {message}");
+                            failed.record(CliError::Conflict(message));
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to unpause container {container_name:?}: {e}");
+                            failed.record(e);
+                        }
+                    }
+                }
+
+                failed.finish("container", "unpause")?;
+                Ok(())
+            }
+            // ./exe ps rename <container_name> <new_name>
+            PsOptions::Rename(RenameContainerInfo {
+                container_name,
+                new_name,
+            }) => {
+                let id = resolve_container(&docker, retry, container_name).await?;
+                let options = RenameContainerOptions {
+                    name: new_name.as_str(),
+                };
+
+                match docker.rename_container(&id, options).await {
+                    Ok(()) => Ok(println!(
+                        "Renamed Container: {container_name:?} to {new_name:?}"
+                    )),
+                    // Another container already has the name
+                    Err(bollard::errors::Error::DockerResponseServerError {
+                        status_code: 409,
+                        message,
+                    }) => Err(CliError::Conflict(message)),
+                    Err(e) => Err(e.into()),
+                }
+            }
+            // ./exe ps update [--memory <size>] [--cpus <n>] [--cpu-shares <n>] [--restart <policy>] <container_name>
+            PsOptions::Update(UpdateContainerInfo {
+                container_name,
+                memory,
+                cpus,
+                cpu_shares,
+                cpuset_cpus,
+                restart,
+            }) => {
+                let id = resolve_container(&docker, retry, container_name).await?;
+                let options = UpdateContainerOptions::<String> {
+                    memory: *memory,
+                    nano_cp_us: cpus.map(|cpus| (cpus * 1e9) as i64),
+                    cpu_shares: *cpu_shares,
+                    cpuset_cpus: cpuset_cpus.clone(),
+                    restart_policy: restart.clone(),
+                    ..Default::default()
+                };
+                docker.update_container(&id, options).await?;
+
+                Ok(println!("Updated Container: {container_name:?}"))
+            }
+            // ./exe ps rm [-f] [-v] <container_name|->...
+            PsOptions::Rm(RemoveContainerInfo {
+                container_names,
+                force,
+                volumes,
+            }) => {
+                let options = RemoveContainerOptions {
+                    force: *force,
+                    v: *volumes,
+                    link: false,
+                };
+
+                let containers = ContainerResolver::new(&docker, retry).await?;
+                let mut failed = Failures::default();
+                for container_name in &read_targets(container_names)? {
+                    let removed = match containers.resolve(container_name) {
+                        Ok(id) => docker
+                            .remove_container(&id, Some(options))
+                            .await
+                            .map_err(CliError::from),
+                        Err(e) => Err(e),
+                    };
+                    match removed {
+                        Ok(_) => println!("Removed Container: {container_name:?}"),
+                        Err(e) => {
+                            eprintln!("Failed to remove container {container_name:?}: {e}");
+                            failed.record(e);
+                        }
+                    }
+                }
+
+                failed.finish("container", "be removed")?;
+                Ok(())
+            }
+            // ./exe ps logs [-f] [--tail N] [-t] [--details] [--prefix[=stream|name]] <container_name>...
+            PsOptions::Logs(LogsInfo {
+                container_names,
+                follow,
+                tail,
+                timestamps,
+                since,
+                until,
+                grep,
+                context,
+                details,
+                prefix,
+            }) => {
+                // The daemon takes Unix seconds, with 0 meaning no bound
+                let options = LogsOptions {
+                    stdout: true,
+                    stderr: true,
+                    follow: *follow,
+                    timestamps: *timestamps,
+                    tail: tail.map_or_else(|| "all".to_string(), |n| n.to_string()),
+                    since: since.map_or(0, |since| since.timestamp()),
+                    until: until.map_or(0, |until| until.timestamp()),
+                    ..Default::default()
+                };
+
+                let containers = ContainerResolver::new(&docker, retry).await?;
+                let mut ids = Vec::new();
+                for container_name in container_names {
+                    ids.push(containers.resolve(container_name)?);
+                }
+                // Interleaved lines are no use without knowing where each came from
+                let prefix = prefix.or((ids.len() > 1).then_some(LogPrefix::Name));
+
+                let mut log_streams = Vec::new();
+                let mut prefixers = Vec::new();
+                for (slot, id) in ids.iter().enumerate() {
+                    let prefixer = if *details || prefix.is_some() {
+                        let inspect = retry
+                            .run("inspect container", || {
+                                docker.inspect_container(id, None::<InspectContainerOptions>)
+                            })
+                            .await?;
+                        let color = colors_enabled()
+                            .then(|| LOG_PREFIX_COLORS[slot % LOG_PREFIX_COLORS.len()]);
+                        Some(LogPrefixer::new(&inspect, prefix, *details, color))
+                    } else {
+                        None
+                    };
+                    prefixers.push(prefixer);
+
+                    let log_stream = docker
+                        .logs(id, Some(options.clone()))
+                        .map(move |frame| frame.map(|frame| (slot, frame)));
+                    log_streams.push(log_stream.boxed());
+                }
+
+                // One merged stream, lines coming out in the order they arrive
+                let log_stream = stream::select_all(log_streams);
+                match grep {
+                    Some(pattern) => {
+                        // Each container gets its own context, so lines around a match all
+                        // come from the container that matched
+                        let mut greps: Vec<LogGrep> = prefixers
+                            .into_iter()
+                            .map(|prefixer| LogGrep::new(pattern.clone(), *context, prefixer))
+                            .collect();
+                        until_interrupted(log_stream, |(slot, frame)| Ok(greps[slot].write(frame)?))
+                            .await?
+                    }
+                    None => {
+                        until_interrupted(log_stream, |(slot, frame)| {
+                            Ok(write_log_frame(apply_prefix(&mut prefixers[slot], frame))?)
+                        })
+                        .await?
+                    }
+                };
+                Ok(())
+            }
+            // ./exe ps exec <container_name> -- <command>...
+            PsOptions::Exec(ExecInfo {
+                container_name,
+                command,
+            }) => {
+                let id = resolve_container(&docker, retry, container_name).await?;
+                let exec = docker
+                    .create_exec(
+                        &id,
+                        CreateExecOptions {
+                            cmd: Some(command.clone()),
+                            attach_stdout: Some(true),
+                            attach_stderr: Some(true),
+                            ..Default::default()
+                        },
+                    )
+                    .await?;
+
+                if let StartExecResults::Attached { mut output, .. } =
+                    docker.start_exec(&exec.id, None).await?
+                {
+                    while let Some(frame) = output.next().await {
+                        write_log_frame(frame?)?;
+                    }
+                }
+
+                // Hand the command's exit status on so scripts can branch on it
+                match docker.inspect_exec(&exec.id).await?.exit_code {
+                    Some(0) | None => Ok(()),
+                    Some(code) => Err(CliError::ExitStatus(u8::try_from(code).unwrap_or(1))),
+                }
+            }
+            // ./exe ps attach [--detach-keys <keys>] [--no-stdin] <container_name>
+            PsOptions::Attach(AttachInfo {
+                container_name,
+                detach_keys,
+                no_stdin,
+            }) => {
+                let id = resolve_container(&docker, retry, container_name).await?;
+                attach::attach(&docker, &id, detach_keys, *no_stdin).await
+            }
+            // ./exe ps stats [--no-stream] [--columns cpu,mem,...] [<container_name>...]
+            PsOptions::Stats(StatsInfo {
+                container_names,
+                no_stream,
+                columns,
+            }) => {
+                let ids = if container_names.is_empty() {
+                    let options = ListContainersOptions::<String>::default();
+                    retry
+                        .run("list containers", || {
+                            docker.list_containers(Some(options.clone()))
+                        })
+                        .await?
+                        .into_iter()
+                        .filter_map(|container| container.id)
+                        .collect()
+                } else {
+                    let containers = ContainerResolver::new(&docker, retry).await?;
+                    let mut ids = Vec::new();
+                    for container_name in container_names {
+                        ids.push(containers.resolve(container_name)?);
+                    }
+                    ids
+                };
+                if ids.is_empty() {
+                    return Ok(eprintln!("No running containers"));
+                }
+
+                let options = StatsOptions {
+                    stream: !*no_stream,
+                    one_shot: false,
+                };
+                // One merged stream, each frame tagged with its container's row
+                let stats_streams = ids.iter().enumerate().map(|(slot, id)| {
+                    docker
+                        .stats(id, Some(options))
+                        .map(move |stats| stats.map(|stats| (slot, stats)))
+                        .boxed()
+                });
+
+                let mut latest: Vec<Option<Stats>> = vec![None; ids.len()];
+                until_interrupted(stream::select_all(stats_streams), |(slot, stats)| {
+                    if cli.output.is_json() {
+                        return print_record(&cli.output, &stats);
+                    }
+                    latest[slot] = Some(stats);
+                    if !*no_stream {
+                        // Clear the screen and redraw from the top left
+                        terminal_control("\x1B[2J\x1B[H");
+                        print_stats_table(latest.iter().flatten(), columns, &cli.output);
+                    }
+                    Ok(())
+                })
+                .await?;
+
+                if *no_stream && !cli.output.is_json() {
+                    print_stats_table(latest.iter().flatten(), columns, &cli.output);
+                }
+                Ok(())
+            }
+            // ./exe ps top [--ps-args <args>] <container_name>
+            PsOptions::Top(TopInfo {
+                container_name,
+                ps_args,
+            }) => {
+                let id = resolve_container(&docker, retry, container_name).await?;
+                let options = ps_args.as_deref().map(|ps_args| TopOptions { ps_args });
+                let top = docker.top_processes(&id, options).await?;
+
+                if cli.output.is_json() {
+                    return print_json(&cli.output, &top);
+                }
+
+                let ContainerTopResponse { titles, processes } = top;
+
+                // Container Top table
+                let mut container_top_table = cli.output.table(Row::new(
+                    titles
+                        .iter()
+                        .flatten()
+                        .map(|title| Cell::new(title).style_spec("b"))
+                        .collect(),
+                ));
+
+                for process in processes.iter().flatten() {
+                    container_top_table.add_row(Row::new(
+                        process.iter().map(|value| Cell::new(value)).collect(),
+                    ));
+                }
+
+                container_top_table.printstd();
+                Ok(())
+            }
+            // ./exe ps cp <container:/path> <local> | <local> <container:/path>
+            PsOptions::Cp(CopyInfo {
+                source,
+                destination,
+            }) => match (source, destination) {
+                (CopyPath::Container { container, path }, CopyPath::Local(dest)) => {
+                    let id = resolve_container(&docker, retry, container).await?;
+                    let options = DownloadFromContainerOptions {
+                        path: path.as_str(),
+                    };
+
+                    let mut archive = Vec::new();
+                    let mut tar_stream = docker.download_from_container(&id, Some(options));
+                    while let Some(chunk) = tar_stream.next().await {
+                        archive.extend_from_slice(&chunk?);
+                    }
+                    copy::unpack(&archive, dest)?;
+
+                    Ok(println!("Copied {container}:{path} to {}", dest.display()))
+                }
+                (CopyPath::Local(source), CopyPath::Container { container, path }) => {
+                    let id = resolve_container(&docker, retry, container).await?;
+                    // `.` and friends have no file name of their own
+                    let name = source
+                        .canonicalize()?
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+
+                    // An existing directory takes the source under its own name; anything else
+                    // (a new path, or a file to overwrite) means unpacking into the parent
+                    // under the destination's name
+                    let options = UploadToContainerOptions {
+                        path: path.as_str(),
+                        ..Default::default()
+                    };
+                    let uploaded = docker
+                        .upload_to_container(&id, Some(options), copy::pack(source, &name)?.into())
+                        .await;
+                    match uploaded {
+                        Err(bollard::errors::Error::DockerResponseServerError {
+                            status_code: 400 | 404,
+                            ..
+                        }) => {
+                            let target = Path::new(path);
+                            let parent = target
+                                .parent()
+                                .map(|parent| parent.to_string_lossy().into_owned())
+                                .filter(|parent| !parent.is_empty())
+                                .unwrap_or_else(|| ".".to_string());
+                            let name = target
+                                .file_name()
+                                .map(|name| name.to_string_lossy().into_owned())
+                                .unwrap_or(name);
+                            let options = UploadToContainerOptions {
+                                path: parent.as_str(),
+                                ..Default::default()
+                            };
+                            let archive = copy::pack(source, &name)?;
+                            docker
+                                .upload_to_container(&id, Some(options), archive.into())
+                                .await?;
+                        }
+                        uploaded => uploaded?,
+                    }
+
+                    Ok(println!(
+                        "Copied {} to {container}:{path}",
+                        source.display()
+                    ))
+                }
+                (CopyPath::Local(_), CopyPath::Local(_)) => Err(CliError::Copy(
+                    "one of SRC and DEST must be a container:/path".to_string(),
+                )),
+                (CopyPath::Container { .. }, CopyPath::Container { .. }) => Err(CliError::Copy(
+                    "copying between two containers isn't supported".to_string(),
+                )),
+            },
+            // ./exe ps port <container_name> [<port>[/<proto>]]
+            PsOptions::Port(PortInfo {
+                container_name,
+                port,
+            }) => {
+                let id = resolve_container(&docker, retry, container_name).await?;
+                let inspect = retry
+                    .run("inspect container", || {
+                        docker.inspect_container(&id, None::<InspectContainerOptions>)
+                    })
+                    .await?;
+
+                // Exposed ports nobody published have no bindings, and `docker port` leaves them out
+                let published: PortMap = inspect
+                    .network_settings
+                    .and_then(|settings| settings.ports)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|(_, bindings)| bindings.as_ref().is_some_and(|b| !b.is_empty()))
+                    .collect();
+
+                let Some(port) = port else {
+                    if cli.output.is_json() {
+                        return print_json(&cli.output, &published);
+                    }
+                    if !published.is_empty() {
+                        println!("{}", format_port_bindings(&published));
+                    }
+                    return Ok(());
+                };
+
+                let bindings = published.get(port).cloned().flatten().ok_or_else(|| {
+                    CliError::PortNotPublished {
+                        port: port.clone(),
+                        container: container_name.clone(),
+                    }
+                })?;
+                if cli.output.is_json() {
+                    return print_json(&cli.output, &bindings);
+                }
+                for PortBinding { host_ip, host_port } in &bindings {
+                    let host_ip = host_ip.as_deref().filter(|ip| !ip.is_empty());
+                    println!(
+                        "{}:{}",
+                        host_ip.unwrap_or("0.0.0.0"),
+                        host_port.as_deref().unwrap_or_default()
+                    );
+                }
+                Ok(())
+            }
+            // ./exe ps diff <container_name>
+            PsOptions::Diff(DiffInfo { container_name }) => {
+                let id = resolve_container(&docker, retry, container_name).await?;
+                let changes = docker.container_changes(&id).await?.unwrap_or_default();
+
+                if cli.output.is_json() {
+                    return print_json_list(&cli.output, &changes);
+                }
+
+                // Container Diff table
+                let mut container_diff_table = cli.output.table(row![b->"Kind", b->"Path"]);
+
+                for FilesystemChange { path, kind } in &changes {
+                    let kind = match kind {
+                        ChangeType::_0 => "C",
+                        ChangeType::_1 => "A",
+                        ChangeType::_2 => "D",
+                    };
+                    container_diff_table.add_row(row![kind, path]);
+                }
+
+                container_diff_table.printstd();
+                Ok(())
+            }
+            // ./exe ps export [--output <file>] <container_name>
+            PsOptions::Export(ExportContainerInfo {
+                container_name,
+                output,
+            }) => {
+                let id = resolve_container(&docker, retry, container_name).await?;
+                let mut archive: Box<dyn Write> = match output {
+                    Some(path) => Box::new(std::fs::File::create(path)?),
+                    None if std::io::IsTerminal::is_terminal(&std::io::stdout()) => {
+                        return Err(CliError::Io(std::io::Error::other(
+                            "refusing to write a container archive to a terminal, use --output or redirect stdout",
+                        )));
+                    }
+                    None => Box::new(std::io::stdout().lock()),
+                };
+
+                // Written chunk by chunk so large filesystems never sit in memory whole
+                let mut written = 0;
+                let mut tar_stream = docker.export_container(&id);
+                while let Some(chunk) = tar_stream.next().await {
+                    let chunk = chunk?;
+                    archive.write_all(&chunk)?;
+                    written += chunk.len();
+                }
+                archive.flush()?;
+
+                let destination = output
+                    .as_ref()
+                    .map_or_else(|| "stdout".to_string(), |path| path.display().to_string());
+                eprintln!(
+                    "Exported Container {container_name:?} to {destination} ({})",
+                    cli.output.size(written as i64)
+                );
+                Ok(())
+            }
+            // ./exe ps inspect [--json] [--env] [--env-grep <regex>] <container_name>
+            PsOptions::Inspect(InspectContainerInfo {
+                container_name,
+                json,
+                env,
+                env_grep,
+            }) => {
+                let id = resolve_container(&docker, retry, container_name).await?;
+                let inspect = retry
+                    .run("inspect container", || {
+                        docker.inspect_container(&id, None::<InspectContainerOptions>)
+                    })
+                    .await?;
+
+                if *env || env_grep.is_some() {
+                    let variables: Vec<String> = inspect
+                        .config
+                        .and_then(|config| config.env)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|variable| {
+                            env_grep
+                                .as_ref()
+                                .map_or(true, |pattern| pattern.is_match(variable))
+                        })
+                        .collect();
+
+                    if *json || cli.output.is_json() {
+                        return print_json_list(&cli.output, &variables);
+                    }
+                    variables.iter().for_each(|variable| println!("{variable}"));
+                    return Ok(());
+                }
+
+                if *json || cli.output.is_json() {
+                    return print_json(&cli.output, &inspect);
+                }
+
+                print_inspect_table(inspect, &cli.output);
+                Ok(())
+            }
+        },
+        Some(Commands::Run(options)) => run::run(&docker, options).await,
+        Some(Commands::Create(options)) => create::run(&docker, options).await,
+        Some(Commands::Compose(options)) => compose::run(&docker, options).await,
+        // ./exe commit [-m <message>] [-a <author>] <container_name> <image>
+        Some(Commands::Commit(CommitInfo {
+            container_name,
+            image,
+            message,
+            author,
+        })) => {
+            let container = resolve_container(&docker, retry, container_name).await?;
+            let (repo, tag) = split_image_ref(image);
+            let options = CommitContainerOptions {
+                container: container.as_str(),
+                repo,
+                tag,
+                comment: message.as_deref().unwrap_or_default(),
+                author: author.as_deref().unwrap_or_default(),
+                // Paused like `docker commit` so the snapshot is consistent
+                pause: true,
+                changes: None,
+            };
+
+            let commit = docker
+                .commit_container(options, ContainerConfig::<String>::default())
+                .await?;
+            if cli.output.is_json() {
+                return print_json(&cli.output, &commit);
+            }
+            Ok(println!("{}", commit.id.unwrap_or_default()))
+        }
+        Some(Commands::Volume(Volume { command })) => {
+            volume::run(&docker, command, &cli.output).await
+        }
+        Some(Commands::Network(Network { command })) => {
+            network::run(&docker, command, &cli.output).await
+        }
+        Some(Commands::System(System { command })) => {
+            system::run(&docker, command, &cli.output).await
+        }
+        Some(Commands::Events(options)) => events::run(&docker, options, &cli.output).await,
+        Some(Commands::Completions(_)) | Some(Commands::Context(_)) | None => {
+            unreachable!("handled before connecting")
+        }
+    }
+}
+
+/// Sends log output to stderr at a level picked by how many times `-d` was given. bollard logs
+/// every request it makes at debug level, so `-dd` shows the API calls behind a command.
+fn init_logging(verbosity: u8) {
+    let level = match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::IsTerminal::is_terminal(&std::io::stderr()))
+        .init();
+}
+
+/// Prints a listing as a table with a bold header row, or as CSV with `--format csv`. Cell
+/// styles only apply to the table.
+fn print_listing(
+    output: &OutputArgs,
+    headers: &[&str],
+    rows: Vec<Vec<Cell>>,
+) -> Result<(), CliError> {
+    if output.format == OutputFormat::Csv {
+        let mut writer = csv::Writer::from_writer(std::io::stdout().lock());
+        if !output.no_headers {
+            writer.write_record(headers)?;
+        }
+        for row in rows {
+            writer.write_record(row.iter().map(Cell::get_content))?;
+        }
+        return Ok(writer.flush()?);
+    }
+
+    let rows = match output.max_width() {
+        Some(max_width) => fit_to_width(headers, rows, max_width),
+        None => rows,
+    };
+
+    let mut table = output.table(Row::new(
+        headers
+            .iter()
+            .map(|header| Cell::new(header).style_spec("b"))
+            .collect(),
+    ));
+    for row in rows {
+        table.add_row(Row::new(row));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+/// Narrows the widest columns until the table fits in `max_width` characters, cutting cells
+/// short with an ellipsis. Columns never get narrower than their header, nor the ID column
+/// narrower than a short ID, so a table that can't fit comes out as narrow as it can.
+fn fit_to_width(headers: &[&str], rows: Vec<Vec<Cell>>, max_width: usize) -> Vec<Vec<Cell>> {
+    let len = |text: &str| {
+        text.lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+
+    let minimums: Vec<usize> = headers
+        .iter()
+        .map(|&header| match header {
+            "ID" => len(header).max(12),
+            header => len(header),
+        })
+        .collect();
+    let mut widths = minimums.clone();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(len(&cell.get_content()));
+        }
+    }
+
+    // Borders and padding: "| " before each column, " " after it, and a closing "|"
+    let overhead = 3 * widths.len() + 1;
+    while widths.iter().sum::<usize>() + overhead > max_width {
+        let Some((widest, _)) = widths
+            .iter()
+            .enumerate()
+            .filter(|&(column, &width)| width > minimums[column])
+            .max_by_key(|&(_, &width)| width)
+        else {
+            break;
+        };
+        widths[widest] -= 1;
+    }
+
+    rows.into_iter()
+        .map(|row| {
+            row.into_iter()
+                .zip(&widths)
+                .map(|(cell, &width)| match cell.get_content() {
+                    content if len(&content) > width => Cell::new(&truncate(&content, width)),
+                    _ => cell,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Points the process's stdout at `path` for `--output-file`, so tables, JSON and streamed
+/// output all end up there without every command having to know about it.
+fn redirect_stdout(path: &Path) -> Result<(), CliError> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::File::create(path)?;
+    std::io::stdout().flush()?;
+    dup_onto_stdout(&file)
+}
+
+#[cfg(unix)]
+fn dup_onto_stdout(file: &std::fs::File) -> Result<(), CliError> {
+    // SAFETY: both descriptors are open; dup2 leaves `file`'s own descriptor to be closed on drop
+    let redirected =
+        unsafe { libc::dup2(std::os::fd::AsRawFd::as_raw_fd(file), libc::STDOUT_FILENO) };
+    if redirected == -1 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn dup_onto_stdout(_file: &std::fs::File) -> Result<(), CliError> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "--output-file is only supported on Unix, redirect stdout with the shell instead",
+    )
+    .into())
+}
+
+/// Sends a terminal control sequence (clearing the screen, hiding the cursor) to stderr, so it
+/// reaches the terminal rather than an `--output-file` or a pipe.
+fn terminal_control(sequence: &str) {
+    let _ = std::io::stdout().flush();
+    let mut stderr = std::io::stderr().lock();
+    let _ = write!(stderr, "{sequence}");
+    let _ = stderr.flush();
+}
+
+/// Prints a single API response as JSON, indented unless `--compact`, or as one record for
+/// `--format jsonl` and templates.
+fn print_json<T: serde::Serialize + ?Sized>(
+    output: &OutputArgs,
+    value: &T,
+) -> Result<(), CliError> {
+    match output.format {
+        OutputFormat::Jsonl | OutputFormat::Template(_) | OutputFormat::Handlebars(_) => {
+            print_record(output, value)
+        }
+        _ => Ok(println!("{}", output.json(value)?)),
+    }
+}
+
+/// Prints a listing as one JSON array, or as one record per item for `--format jsonl`
+/// and templates.
+fn print_json_list<T: serde::Serialize>(output: &OutputArgs, items: &[T]) -> Result<(), CliError> {
+    match output.format {
+        OutputFormat::Jsonl | OutputFormat::Template(_) | OutputFormat::Handlebars(_) => {
+            items.iter().try_for_each(|item| print_record(output, item))
+        }
+        _ => Ok(println!("{}", output.json(items)?)),
+    }
+}
+
+/// Writes one item of a listing or stream on its own line, through the `--format` template if
+/// there is one and as compact JSON otherwise, and flushes it straight away so consumers reading
+/// a pipe see each record as soon as it's produced.
+fn print_record<T: serde::Serialize + ?Sized>(
+    output: &OutputArgs,
+    value: &T,
+) -> Result<(), CliError> {
+    let line = match &output.format {
+        OutputFormat::Template(template) => {
+            template::render(template, &serde_json::to_value(value)?)?
+        }
+        OutputFormat::Handlebars(template) => {
+            template::render_handlebars(template, &serde_json::to_value(value)?)?
+        }
+        _ => serde_json::to_string(value)?,
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{line}")?;
+    Ok(stdout.flush()?)
+}
+
+/// Prints the `ps info` listing once.
+async fn print_containers(
+    docker: &Docker,
+    retry: Retry,
+    info: &ListContainersInfo,
+    output: &OutputArgs,
+) -> Result<(), CliError> {
+    let ListContainersInfo {
+        quiet,
+        all,
+        filters,
+        image,
+        sort,
+        last,
+        latest,
+        since,
+        before,
+        size,
+        mounts,
+        command: show_command,
+        labels,
+        inspect,
+        long,
+        concurrency,
+        exit_code,
+        ..
+    } = info;
+
+    let mut list_container_filters = filter_map(filters);
+    if let Some(image) = image {
+        list_container_filters
+            .entry("ancestor".to_string())
+            .or_default()
+            .push(image.clone());
+    }
+    let last = if *latest { Some(1) } else { *last };
+
+    // The daemon wants the anchors as exact names or IDs, so short prefixes are resolved first
+    for (key, anchor) in [("since", since), ("before", before)] {
+        if let Some(anchor) = anchor {
+            let id = resolve_container(docker, retry, anchor).await?;
+            list_container_filters.insert(key.to_string(), vec![id]);
+        }
+    }
+
+    // Only running containers unless asked otherwise, like `docker ps`
+    let anchored = since.is_some() || before.is_some();
+    if !*all && last.is_none() && !anchored && !list_container_filters.contains_key("status") {
+        list_container_filters.insert("status".to_string(), vec!["running".to_string()]);
+    }
+
+    let options = ListContainersOptions {
+        all: true,
+        filters: list_container_filters,
+        size: *size,
+        ..Default::default()
+    };
+    let mut containers = retry
+        .run("list containers", || {
+            docker.list_containers(Some(options.clone()))
+        })
+        .await?;
+    if let Some(last) = last {
+        sort_containers(&mut containers, ContainerSort::Created);
+        containers.truncate(last);
+    }
+    sort_containers(&mut containers, *sort);
+    let containers = &containers;
+    let found = match *exit_code && containers.is_empty() {
+        true => Err(CliError::NoMatches),
+        false => Ok(()),
+    };
+
+    if *quiet {
+        containers
+            .iter()
+            .filter_map(|container| container.id.as_deref())
+            .for_each(|id| println!("{id}"));
+        return found;
+    }
+
+    if containers.is_empty() && output.is_table() {
+        println!("No containers found.");
+        return found;
+    }
+
+    if *inspect {
+        // `buffered` keeps up to N inspections in flight but yields them in listing order
+        let inspected: Vec<ContainerInspectResponse> = stream::iter(containers)
+            .map(|container| conc(docker, retry, container, true))
+            .buffered(usize::from(*concurrency))
+            .try_collect()
+            .await?;
+
+        if output.is_json() {
+            print_json_list(output, &inspected)?;
+            return found;
+        }
+        for inspect in inspected {
+            print_inspect_table(inspect, output);
+        }
+        return found;
+    }
+
+    if output.is_json() {
+        print_json_list(output, containers)?;
+        return found;
+    }
+
+    // Exit codes and restart counts aren't in the summary, only in each container's inspect
+    let inspected: Vec<ContainerInspectResponse> = if *long {
+        stream::iter(containers)
+            .map(|container| conc(docker, retry, container, false))
+            .buffered(usize::from(*concurrency))
+            .try_collect()
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    // Container Summary listing
+    let mounts = *mounts || output.format == OutputFormat::Wide;
+    let show_command = *show_command || output.format == OutputFormat::Wide;
+    let mut container_summary_rows = Vec::new();
+
+    for (
+        index,
+        ContainerSummary {
+            id,
+            names,
+            image,
+            command,
+            created,
+            state,
+            status,
+            ports,
+            size_rw,
+            size_root_fs,
+            mounts: container_mounts,
+            labels: container_labels,
+            ..
+        },
+    ) in containers.iter().enumerate()
+    {
+        let mut row = vec![
+            Cell::new(output.id(id.as_deref().unwrap_or(""))),
+            Cell::new(
+                names
+                    .as_ref()
+                    .map_or_else(|| "n/a".to_string(), |vec| vec.join(", "))
+                    .strip_prefix("/")
+                    .unwrap_or_else(|| "n/a"),
+            ),
+            Cell::new(output.image(image.as_deref().unwrap_or(""))),
+        ];
+        if show_command {
+            row.push(Cell::new(
+                &output.text(command.as_deref().unwrap_or(""), 20),
+            ));
+        }
+        row.extend([
+            Cell::new(&created.map_or_else(|| "-".to_string(), |c| output.time(c))),
+            state_cell(state.as_deref().unwrap_or("")),
+            health_cell(container_health(status.as_deref().unwrap_or(""))),
+        ]);
+        if let Some(ContainerInspectResponse {
+            state,
+            restart_count,
+            ..
+        }) = inspected.get(index)
+        {
+            // Running containers haven't exited yet, whatever the last run left behind
+            let exit_code = state
+                .as_ref()
+                .filter(|state| state.running != Some(true))
+                .and_then(|state| state.exit_code)
+                .map_or_else(|| "-".to_string(), |code| code.to_string());
+            row.push(Cell::new(&exit_code));
+            row.push(Cell::new(&restart_count.unwrap_or_default().to_string()));
+        }
+        row.push(Cell::new(&format_ports(
+            ports.as_deref().unwrap_or_default(),
+        )));
+        if *size {
+            for bytes in [size_rw, size_root_fs] {
+                row.push(Cell::new(
+                    &bytes.map_or_else(|| "-".to_string(), |b| output.size(b)),
+                ));
+            }
+        }
+        if mounts {
+            row.push(Cell::new(&format_mounts(
+                container_mounts.as_deref().unwrap_or_default(),
+            )));
+        }
+        for label in labels {
+            // Containers without the label get an empty cell
+            let value = container_labels
+                .as_ref()
+                .and_then(|container_labels| container_labels.get(label))
+                .map_or("", String::as_str);
+            row.push(Cell::new(value));
+        }
+        container_summary_rows.push(row);
+    }
+
+    let mut headers = vec!["ID", "Container Name", "Image"];
+    if show_command {
+        headers.push("Command");
+    }
+    headers.extend(["Created", "State", "Health"]);
+    if *long {
+        headers.extend(["Exit", "Restarts"]);
+    }
+    headers.push("Ports");
+    if *size {
+        headers.extend(["Size", "Virtual"]);
+    }
+    if mounts {
+        headers.push("Mounts");
+    }
+    headers.extend(labels.iter().map(String::as_str));
+    print_listing(output, &headers, container_summary_rows)?;
+    found
+}
+
+/// The `img list` table rows, with a Digest column when `digests` is set.
+fn image_rows(images: &[ImageSummary], output: &OutputArgs, digests: bool) -> Vec<Vec<Cell>> {
+    let wide = output.format == OutputFormat::Wide;
+    let mut image_summary_rows = Vec::new();
+
+    for ImageSummary {
+        id,
+        size,
+        repo_tags,
+        repo_digests,
+        created,
+        ..
+    } in images
+    {
+        let repo_tag = display_repo_tag(repo_tags, wide);
+
+        let mut row = vec![Cell::new(output.id(id)), Cell::new(&repo_tag)];
+        if digests {
+            // Images built locally and never pushed have no digest
+            let digest = repo_digests
+                .first()
+                .and_then(|digest| digest.split_once('@'))
+                .map_or("<none>", |(_, digest)| digest);
+            row.push(Cell::new(digest));
+        }
+        row.push(Cell::new(&output.time(*created)));
+        row.push(Cell::new(&output.size(*size)));
+        image_summary_rows.push(row);
+    }
+    image_summary_rows
+}
+
+/// The Image Tag cell: the first tag, all of them with `--format wide`, or `<none>` for a
+/// dangling image, which comes back with no repo tags at all (or `<none>:<none>` from older
+/// daemons).
+fn display_repo_tag(repo_tags: &[String], wide: bool) -> String {
+    let tags: Vec<&str> = repo_tags
+        .iter()
+        .map(String::as_str)
+        .filter(|tag| *tag != "<none>:<none>")
+        .collect();
+    match tags.first() {
+        None => "<none>".to_string(),
+        Some(_) if wide => tags.join(", "),
+        Some(tag) => tag.to_string(),
+    }
+}
+
+/// Sorts images in place so listings come out in a stable order.
+fn sort_images(images: &mut [ImageSummary], sort: ImageSort) {
+    match sort {
+        ImageSort::Size => images.sort_by(|a, b| b.size.cmp(&a.size).then(a.id.cmp(&b.id))),
+        ImageSort::Created => {
+            images.sort_by(|a, b| b.created.cmp(&a.created).then(a.id.cmp(&b.id)))
+        }
+        ImageSort::Repo => images.sort_by(|a, b| {
+            a.repo_tags
+                .first()
+                .cmp(&b.repo_tags.first())
+                .then(a.id.cmp(&b.id))
+        }),
+    }
+}
+
+/// Sorts containers in place so listings come out in a stable order.
+fn sort_containers(containers: &mut [ContainerSummary], sort: ContainerSort) {
+    match sort {
+        ContainerSort::Name => containers.sort_by(|a, b| container_name(a).cmp(container_name(b))),
+        ContainerSort::Created => containers.sort_by(|a, b| {
+            b.created
+                .cmp(&a.created)
+                .then(container_name(a).cmp(container_name(b)))
+        }),
+        ContainerSort::State => containers.sort_by(|a, b| {
+            a.state
+                .cmp(&b.state)
+                .then(container_name(a).cmp(container_name(b)))
+        }),
+    }
+}
+
+/// Shortens an ID to the 12-character form the Docker CLI displays, dropping any `sha256:`
+/// prefix. Counts characters rather than bytes, so odd or short IDs never cause a bad slice.
+fn short_id(id: &str) -> &str {
+    let id = id.strip_prefix("sha256:").unwrap_or(id);
+    match id.char_indices().nth(12) {
+        Some((end, _)) => &id[..end],
+        None => id,
+    }
+}
+
+/// Cuts `text` down to at most `max` characters, marking the cut with an ellipsis.
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(max.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Renders a byte count in decimal units the way the Docker CLI does (`1.2GB`, `340MB`, `512KB`).
+fn human_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 6] = ["B", "KB", "MB", "GB", "TB", "PB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size.abs() >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{bytes}B"),
+        _ if size.abs() < 10.0 => format!("{size:.1}{}", UNITS[unit]),
+        _ => format!("{size:.0}{}", UNITS[unit]),
+    }
+}
+
+/// Renders a duration the way the Docker CLI does (`3 hours`, `About a minute`, `2 weeks`).
+fn human_duration(duration: chrono::Duration) -> String {
+    let seconds = duration.num_seconds();
+    let hours = duration.num_hours();
+    match seconds {
+        s if s < 1 => "Less than a second".to_string(),
+        1 => "1 second".to_string(),
+        s if s < 60 => format!("{s} seconds"),
+        s if s < 120 => "About a minute".to_string(),
+        s if s < 3600 => format!("{} minutes", s / 60),
+        _ if hours < 2 => "About an hour".to_string(),
+        _ if hours < 48 => format!("{hours} hours"),
+        _ if hours < 24 * 7 * 2 => format!("{} days", hours / 24),
+        _ if hours < 24 * 30 * 2 => format!("{} weeks", hours / 24 / 7),
+        _ if hours < 24 * 365 * 2 => format!("{} months", hours / 24 / 30),
+        _ => format!("{} years", hours / 24 / 365),
+    }
+}
+
+/// Whether output may be colored: stdout is a terminal and `NO_COLOR` isn't set.
+fn colors_enabled() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    !no_color && std::io::IsTerminal::is_terminal(&std::io::stdout())
+}
+
+/// A container state, colored by what it means for the container when colors are enabled.
+fn state_cell(state: &str) -> Cell {
+    let cell = Cell::new(state);
+    if !colors_enabled() {
+        return cell;
+    }
+
+    match state {
+        "running" => cell.style_spec("Fg"),
+        "exited" | "dead" => cell.style_spec("Fr"),
+        "paused" => cell.style_spec("Fy"),
+        "created" | "restarting" => cell.style_spec("Fc"),
+        _ => cell,
+    }
+}
+
+/// The healthcheck result the daemon appends to a container's status, e.g. `Up 5 minutes
+/// (unhealthy)`, or `-` for containers without a healthcheck.
+fn container_health(status: &str) -> &'static str {
+    if status.ends_with("(healthy)") {
+        "healthy"
+    } else if status.ends_with("(unhealthy)") {
+        "unhealthy"
+    } else if status.ends_with("(health: starting)") {
+        "starting"
+    } else {
+        "-"
+    }
+}
+
+/// A healthcheck result, colored like `state_cell` when colors are enabled.
+fn health_cell(health: &str) -> Cell {
+    let cell = Cell::new(health);
+    if !colors_enabled() {
+        return cell;
+    }
+
+    match health {
+        "healthy" => cell.style_spec("Fg"),
+        "unhealthy" => cell.style_spec("Fr"),
+        "starting" => cell.style_spec("Fc"),
+        _ => cell,
+    }
+}
+
+/// Renders the ports of an inspected container one per line, as `80/tcp -> 0.0.0.0:8080`, or
+/// `80/tcp ->` for a port that is exposed but not published.
+fn format_port_bindings(ports: &PortMap) -> String {
+    let mut container_ports: Vec<_> = ports.iter().collect();
+    container_ports.sort_by_key(|(port, _)| {
+        let (number, proto) = port.split_once('/').unwrap_or((port.as_str(), ""));
+        (number.parse::<u16>().unwrap_or_default(), proto.to_string())
+    });
+
+    let mut rendered = Vec::new();
+    for (port, bindings) in container_ports {
+        match bindings.as_deref() {
+            Some(bindings) if !bindings.is_empty() => {
+                for PortBinding { host_ip, host_port } in bindings {
+                    let host_ip = host_ip.as_deref().filter(|ip| !ip.is_empty());
+                    rendered.push(format!(
+                        "{port} -> {}:{}",
+                        host_ip.unwrap_or("0.0.0.0"),
+                        host_port.as_deref().unwrap_or_default()
+                    ));
+                }
+            }
+            _ => rendered.push(format!("{port} ->")),
+        }
+    }
+
+    if rendered.is_empty() {
+        return "-".to_string();
+    }
+    rendered.join("\n")
+}
+
+/// Renders port mappings like `docker ps` does (`0.0.0.0:8080->80/tcp`, or `80/tcp` when
+/// unpublished). Repeats, including the `::` twin the daemon adds for every IPv4 binding, are
+/// only shown once.
+fn format_ports(ports: &[Port]) -> String {
+    let mut ports = ports.to_vec();
+    ports.sort_by_key(|port| (port.private_port, port.public_port, port.ip.clone()));
+
+    let mut rendered: Vec<String> = Vec::new();
+    for Port {
+        ip,
+        private_port,
+        public_port,
+        typ,
+    } in &ports
+    {
+        let proto = typ
+            .map(|typ| typ.to_string())
+            .filter(|typ| !typ.is_empty())
+            .unwrap_or_else(|| "tcp".to_string());
+        let port = match (ip.as_deref(), public_port) {
+            (_, None) => format!("{private_port}/{proto}"),
+            // Shown as the IPv4 wildcard so both halves of a dual-stack binding collapse
+            (Some("::") | None, Some(public)) => {
+                format!("0.0.0.0:{public}->{private_port}/{proto}")
+            }
+            (Some(ip), Some(public)) if ip.contains(':') => {
+                format!("[{ip}]:{public}->{private_port}/{proto}")
+            }
+            (Some(ip), Some(public)) => format!("{ip}:{public}->{private_port}/{proto}"),
+        };
+        if !rendered.contains(&port) {
+            rendered.push(port);
+        }
+    }
+    rendered.join(", ")
+}
+
+/// Volume names and bind-mount sources, each with where it's mounted in the container.
+fn format_mounts(mounts: &[MountPoint]) -> String {
+    mounts
+        .iter()
+        .map(|mount| {
+            let source = mount
+                .name
+                .as_deref()
+                .or(mount.source.as_deref())
+                .unwrap_or("");
+            let destination = mount.destination.as_deref().unwrap_or("");
+            format!("{source}:{destination}")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The primary name of a container without the daemon's leading slash.
+fn container_name(container: &ContainerSummary) -> &str {
+    container
+        .names
+        .as_ref()
+        .and_then(|names| names.first())
+        .map_or("", |name| name.strip_prefix('/').unwrap_or(name))
+}
+
+/// Reads the tarball for `img load` or `img import` from `path`, or stdin when there's none.
+///
+/// The whole archive ends up in memory: bollard 0.16 only takes these request bodies as
+/// `Bytes`, with no way to stream them from a reader.
+fn read_archive(path: Option<&Path>) -> std::io::Result<Vec<u8>> {
+    match path {
+        Some(path) => std::fs::read(path),
+        None => {
+            let mut archive = Vec::new();
+            std::io::Read::read_to_end(&mut std::io::stdin().lock(), &mut archive)?;
+            Ok(archive)
+        }
+    }
+}
+
+/// Asks the user to confirm a destructive operation, defaulting to no. The prompt goes to
+/// stderr so it is seen even when stdout is a file or a pipe.
+fn confirm(warning: &str) -> std::io::Result<bool> {
+    eprint!("WARNING! {warning}\nAre you sure you want to continue? [y/N] ");
+    std::io::stderr().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "Yes"))
+}
+
+/// Expands `-` targets into the names or IDs piped on stdin, one per line, so the output of
+/// `ps info -q` can be fed straight back in. Blank lines are skipped.
+fn read_targets(targets: &[String]) -> Result<Vec<String>, CliError> {
+    let mut expanded = Vec::new();
+    for target in targets {
+        if target != "-" {
+            expanded.push(target.clone());
+            continue;
+        }
+        for line in std::io::stdin().lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                expanded.push(line.trim().to_string());
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+/// Parses a `--filter key=value` argument into its key and value.
+fn parse_filter(filter: &str) -> Result<(String, String), String> {
+    match filter.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected key=value, got {filter:?}")),
+    }
+}
+
+/// Groups parsed `--filter` arguments by key, the shape the daemon's `filters` parameters take.
+fn filter_map(filters: &[(String, String)]) -> HashMap<String, Vec<String>> {
+    let mut filter_map: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, value) in filters {
+        filter_map
+            .entry(key.clone())
+            .or_default()
+            .push(value.clone());
+    }
+    filter_map
+}
+
+/// Parses a point in time given as an RFC3339 timestamp, as Unix seconds, or as a duration
+/// before now like `10m` or `1h30m`.
+fn parse_timestamp(time: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(secs) = time.parse::<i64>() {
+        return DateTime::from_timestamp(secs, 0).ok_or_else(|| format!("{time} is out of range"));
+    }
+    if let Some(ago) = parse_duration(time) {
+        return Ok(Utc::now() - ago);
+    }
+
+    DateTime::parse_from_rfc3339(time)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| format!("expected an RFC3339 or Unix timestamp, or a duration like 10m: {e}"))
+}
+
+/// Parses durations like `90s`, `10m`, `1h30m` or `2d`, `None` meaning it isn't one.
+fn parse_duration(duration: &str) -> Option<chrono::Duration> {
+    let mut total = chrono::Duration::zero();
+    let mut rest = duration;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        total += match unit {
+            's' => chrono::Duration::try_seconds(amount)?,
+            'm' => chrono::Duration::try_minutes(amount)?,
+            'h' => chrono::Duration::try_hours(amount)?,
+            'd' => chrono::Duration::try_days(amount)?,
+            _ => return None,
+        };
+        rest = &rest[digits + 1..];
+    }
+    (!duration.is_empty()).then_some(total)
+}
+
+/// Parses a memory size like `512m` or `2g` into bytes. Units are binary, as with the Docker
+/// CLI, and a bare number is bytes.
+fn parse_memory(size: &str) -> Result<i64, String> {
+    let error = || format!("expected a size like 512m or 2g, got {size:?}");
+    let lower = size.to_ascii_lowercase();
+    let digits = lower.trim_end_matches(['b', 'k', 'm', 'g', 't']);
+    let unit: i64 = match lower[digits.len()..].trim_end_matches('b') {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        _ => return Err(error()),
+    };
+
+    let amount: f64 = digits.parse().map_err(|_| error())?;
+    if !amount.is_finite() || amount < 0.0 {
+        return Err(error());
+    }
+    Ok((amount * unit as f64) as i64)
+}
+
+/// Compiles the reference pattern of `img retag`.
+fn parse_reference_pattern(pattern: &str) -> Result<glob::Pattern, String> {
+    glob::Pattern::new(pattern).map_err(|e| format!("invalid pattern {pattern:?}: {e}"))
+}
+
+/// Checks the target of `img retag` ends in the `*` the matched names go in, and drops it.
+fn parse_retag_target(target: &str) -> Result<String, String> {
+    match target.strip_suffix('*') {
+        Some(prefix) if !prefix.contains(['*', '?', '[']) => Ok(prefix.to_string()),
+        _ => Err(format!(
+            "expected a prefix ending in a single * (e.g. registry.example.com/myapp/*), got {target:?}"
+        )),
+    }
+}
+
+/// Normalizes the `<port>[/<proto>]` of `ps port` to the `80/tcp` form the daemon keys
+/// port mappings by.
+fn parse_container_port(port: &str) -> Result<String, String> {
+    let (number, proto) = port.split_once('/').unwrap_or((port, "tcp"));
+    if number.parse::<u16>().is_err() {
+        return Err(format!("invalid port number {number:?}"));
+    }
+    match proto.to_lowercase().as_str() {
+        proto @ ("tcp" | "udp" | "sctp") => Ok(format!("{number}/{proto}")),
+        _ => Err(format!(
+            "unknown protocol {proto:?}, expected tcp, udp or sctp"
+        )),
+    }
+}
+
+/// Checks a `--platform` is `os/arch` or `os/arch/variant`, e.g. `linux/arm64/v8`.
+fn parse_platform(platform: &str) -> Result<String, String> {
+    let parts: Vec<&str> = platform.split('/').collect();
+    let well_formed = (2..=3).contains(&parts.len())
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+    if !well_formed {
+        return Err(format!(
+            "expected os/arch[/variant] (e.g. linux/arm64), got {platform:?}"
+        ));
+    }
+    Ok(platform.to_lowercase())
+}
+
+/// Parses a restart policy the way `docker run --restart` takes it.
+fn parse_restart_policy(policy: &str) -> Result<RestartPolicy, String> {
+    let (name, retries) = match policy.split_once(':') {
+        Some((name, retries)) => (name, Some(retries)),
+        None => (policy, None),
+    };
+
+    let name = match name {
+        "no" => RestartPolicyNameEnum::NO,
+        "always" => RestartPolicyNameEnum::ALWAYS,
+        "unless-stopped" => RestartPolicyNameEnum::UNLESS_STOPPED,
+        "on-failure" => RestartPolicyNameEnum::ON_FAILURE,
+        _ => return Err(format!("unknown restart policy {name:?}")),
+    };
+    let maximum_retry_count = match retries {
+        Some(retries) if name == RestartPolicyNameEnum::ON_FAILURE => Some(
+            retries
+                .parse()
+                .map_err(|_| format!("expected a number of retries, got {retries:?}"))?,
+        ),
+        Some(_) => return Err("only on-failure takes a maximum retry count".to_string()),
+        None => None,
+    };
+
+    Ok(RestartPolicy {
+        name: Some(name),
+        maximum_retry_count,
+    })
+}
+
+/// Compiles a `--grep` pattern up front, so a bad one is a usage error.
+fn parse_regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| e.to_string())
+}
+
+/// Validates a container name against the daemon's rules: `[a-zA-Z0-9][a-zA-Z0-9_.-]+`, so at
+/// least two characters starting with a letter or digit.
+fn parse_container_name(name: &str) -> Result<String, String> {
+    let name = name.strip_prefix('/').unwrap_or(name);
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && name.len() >= 2
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err(format!(
+            "{name:?} is not a valid container name, only [a-zA-Z0-9][a-zA-Z0-9_.-]+ is allowed"
+        ))
+    }
+}
+
+/// Signal names understood by the daemon's kill endpoint.
+const SIGNALS: &[&str] = &[
+    "SIGABRT",
+    "SIGALRM",
+    "SIGBUS",
+    "SIGCHLD",
+    "SIGCONT",
+    "SIGFPE",
+    "SIGHUP",
+    "SIGILL",
+    "SIGINT",
+    "SIGIO",
+    "SIGKILL",
+    "SIGPIPE",
+    "SIGPROF",
+    "SIGPWR",
+    "SIGQUIT",
+    "SIGSEGV",
+    "SIGSTKFLT",
+    "SIGSTOP",
+    "SIGSYS",
+    "SIGTERM",
+    "SIGTRAP",
+    "SIGTSTP",
+    "SIGTTIN",
+    "SIGTTOU",
+    "SIGURG",
+    "SIGUSR1",
+    "SIGUSR2",
+    "SIGVTALRM",
+    "SIGWINCH",
+    "SIGXCPU",
+    "SIGXFSZ",
+];
+
+/// Validates a `--signal` value, accepting numeric signals and names with or without the `SIG`
+/// prefix (`kill`, `SIGKILL` and `9` are all fine).
+fn parse_signal(signal: &str) -> Result<String, String> {
+    if let Ok(number) = signal.parse::<u8>() {
+        return match number {
+            1..=64 => Ok(number.to_string()),
+            _ => Err(format!("signal number {number} is out of range")),
+        };
+    }
+
+    let upper = signal.to_ascii_uppercase();
+    let name = if upper.starts_with("SIG") {
+        upper
+    } else {
+        format!("SIG{upper}")
+    };
+
+    if SIGNALS.contains(&name.as_str()) {
+        Ok(name)
+    } else {
+        Err(format!("unknown signal {signal:?}"))
+    }
+}
+
+/// Splits an image reference into its repository and tag (or digest), defaulting the tag to
+/// `latest` when none is given.
+fn split_image_ref(reference: &str) -> (&str, &str) {
+    if let Some((repo, digest)) = reference.split_once('@') {
+        return (repo, digest);
+    }
+
+    match reference.rsplit_once(':') {
+        // A colon followed by a slash belongs to a registry port, not a tag
+        Some((repo, tag)) if !tag.contains('/') => (repo, tag),
+        _ => (reference, "latest"),
+    }
+}
+
+/// Hands each item of `stream` to `handle` until the stream ends or the user hits Ctrl-C,
+/// returning `true` when it was interrupted.
+///
+/// Items are handled to completion before the interrupt is looked at, so the last line printed
+/// is never cut short, and both output streams are flushed on the way out.
+async fn until_interrupted<S, T, E>(
+    stream: S,
+    mut handle: impl FnMut(T) -> Result<(), CliError>,
+) -> Result<bool, CliError>
+where
+    S: futures_util::Stream<Item = Result<T, E>>,
+    CliError: From<E>,
+{
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    tokio::pin!(stream);
+
+    let interrupted = loop {
+        tokio::select! {
+            _ = &mut ctrl_c => break true,
+            item = stream.next() => match item {
+                Some(item) => handle(item?)?,
+                None => break false,
+            },
+        }
+    };
+
+    std::io::stdout().flush()?;
+    std::io::stderr().flush()?;
+    Ok(interrupted)
+}
+
+/// Writes a log frame to the stream it came from, so shell redirection of stdout and stderr
+/// keeps working.
+fn write_log_frame(frame: LogOutput) -> std::io::Result<()> {
+    match frame {
+        LogOutput::StdErr { message } => {
+            let mut stderr = std::io::stderr().lock();
+            stderr.write_all(&message)?;
+            stderr.flush()
+        }
+        frame => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(&frame.into_bytes())?;
+            stdout.flush()
+        }
+    }
+}
+
+/// How many containers `ps logs` streams from at once, each holding a connection to the daemon
+/// open for as long as it follows.
+const MAX_LOG_STREAMS: usize = 16;
+
+/// Cyan, yellow, green, magenta, blue and red, cycled through for `ps logs` container names.
+const LOG_PREFIX_COLORS: [u8; 6] = [36, 33, 32, 35, 34, 31];
+
+/// `ps logs --prefix` and `--details`: puts the stream or container name, and the log
+/// attributes, in front of every line. Frames don't always end on a line break, so where each
+/// stream's next line starts is tracked across frames.
+struct LogPrefixer {
+    prefix: Option<LogPrefix>,
+    name: String,
+    /// ANSI color the name is shown in, so interleaved containers are told apart at a glance
+    color: Option<u8>,
+    /// `key=value,...` from the log options, empty without `--details`
+    details: String,
+    /// Whether stdout, stderr, stdin and console are each at the start of a line
+    line_start: [bool; 4],
+}
+
+impl LogPrefixer {
+    fn new(
+        inspect: &ContainerInspectResponse,
+        prefix: Option<LogPrefix>,
+        details: bool,
+        color: Option<u8>,
+    ) -> LogPrefixer {
+        let name = inspect
+            .name
+            .as_deref()
+            .unwrap_or_default()
+            .trim_start_matches('/')
+            .to_string();
+        LogPrefixer {
+            prefix,
+            name,
+            color,
+            details: if details {
+                log_details(inspect)
+            } else {
+                String::new()
+            },
+            line_start: [true; 4],
+        }
+    }
+
+    fn apply(&mut self, frame: LogOutput) -> LogOutput {
+        let (slot, stream, message) = match &frame {
+            LogOutput::StdOut { message } => (0, "stdout", message),
+            LogOutput::StdErr { message } => (1, "stderr", message),
+            LogOutput::StdIn { message } => (2, "stdin", message),
+            LogOutput::Console { message } => (3, "console", message),
+        };
+
+        let mut label = match self.prefix {
+            Some(LogPrefix::Stream) => format!("[{stream}] "),
+            Some(LogPrefix::Name) => match self.color {
+                Some(color) => format!("\x1B[{color}m[{}]\x1B[0m ", self.name),
+                None => format!("[{}] ", self.name),
+            },
+            None => String::new(),
+        };
+        if !self.details.is_empty() {
+            label.push_str(&self.details);
+            label.push(' ');
+        }
+
+        let mut prefixed = Vec::with_capacity(message.len() + label.len());
+        for line in message.split_inclusive(|byte| *byte == b'\n') {
+            if self.line_start[slot] {
+                prefixed.extend_from_slice(label.as_bytes());
+            }
+            prefixed.extend_from_slice(line);
+            self.line_start[slot] = line.ends_with(b"\n");
+        }
+
+        let message = prefixed.into();
+        match frame {
+            LogOutput::StdOut { .. } => LogOutput::StdOut { message },
+            LogOutput::StdErr { .. } => LogOutput::StdErr { message },
+            LogOutput::StdIn { .. } => LogOutput::StdIn { message },
+            LogOutput::Console { .. } => LogOutput::Console { message },
+        }
+    }
+}
+
+/// Runs `frame` through the stream's prefixer, if it has one.
+fn apply_prefix(prefixer: &mut Option<LogPrefixer>, frame: LogOutput) -> LogOutput {
+    match prefixer {
+        Some(prefixer) => prefixer.apply(frame),
+        None => frame,
+    }
+}
+
+/// The attributes the daemon's `details` logs option would add, worked out from the container's
+/// log options the same way: labels named in `labels` or matching `labels-regex`, and variables
+/// named in `env` or matching `env-regex`, as sorted `key=value` pairs joined by commas.
+fn log_details(inspect: &ContainerInspectResponse) -> String {
+    let log_options = inspect
+        .host_config
+        .as_ref()
+        .and_then(|host_config| host_config.log_config.as_ref())
+        .and_then(|log_config| log_config.config.clone())
+        .unwrap_or_default();
+    let config = inspect.config.clone().unwrap_or_default();
+
+    let named = |option: &str| -> Vec<String> {
+        log_options
+            .get(option)
+            .map(|names| names.split(',').map(str::to_string).collect())
+            .unwrap_or_default()
+    };
+    let pattern = |option: &str| log_options.get(option).and_then(|re| Regex::new(re).ok());
+
+    let mut attributes = std::collections::BTreeMap::new();
+    let (label_names, label_pattern) = (named("labels"), pattern("labels-regex"));
+    for (key, value) in config.labels.unwrap_or_default() {
+        if label_names.contains(&key) || label_pattern.as_ref().is_some_and(|re| re.is_match(&key))
+        {
+            attributes.insert(key, value);
+        }
+    }
+    let (env_names, env_pattern) = (named("env"), pattern("env-regex"));
+    for variable in config.env.unwrap_or_default() {
+        let Some((key, value)) = variable.split_once('=') else {
+            continue;
+        };
+        if env_names.iter().any(|name| name == key)
+            || env_pattern.as_ref().is_some_and(|re| re.is_match(key))
+        {
+            attributes.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    attributes
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// `ps logs --grep`: passes matching log frames on, with `--context` frames either side of each
+/// match, and `--` between groups that aren't adjacent, the way `grep -C` does.
+///
+/// The pattern is matched against the log line as the container wrote it, and the prefix is only
+/// put on the frames that get through.
+struct LogGrep {
+    pattern: Regex,
+    context: usize,
+    prefixer: Option<LogPrefixer>,
+    /// The most recent unprinted frames, shown if a match follows
+    before: VecDeque<LogOutput>,
+    /// How many more frames to show after the last match
+    after: usize,
+    /// Whether any frame has been printed yet, and whether frames were dropped since
+    printed: bool,
+    skipped: bool,
+}
+
+impl LogGrep {
+    fn new(pattern: Regex, context: usize, prefixer: Option<LogPrefixer>) -> LogGrep {
+        LogGrep {
+            pattern,
+            context,
+            prefixer,
+            before: VecDeque::with_capacity(context + 1),
+            after: 0,
+            printed: false,
+            skipped: false,
+        }
+    }
+
+    fn write(&mut self, frame: LogOutput) -> std::io::Result<()> {
+        for frame in self.take(frame) {
+            write_log_frame(frame)?;
+        }
+        Ok(())
+    }
+
+    /// The frames to print now that `frame` has come in, prefixed, with a `--` frame ahead of a
+    /// group that doesn't follow on from the last one.
+    fn take(&mut self, frame: LogOutput) -> Vec<LogOutput> {
+        let (LogOutput::StdErr { message }
+        | LogOutput::StdOut { message }
+        | LogOutput::StdIn { message }
+        | LogOutput::Console { message }) = &frame;
+
+        let mut out = Vec::new();
+        if self.pattern.is_match(&String::from_utf8_lossy(message)) {
+            if self.context > 0 && self.printed && self.skipped {
+                out.push(LogOutput::StdOut {
+                    message: "--\n".into(),
+                });
+            }
+            for frame in self.before.drain(..) {
+                out.push(apply_prefix(&mut self.prefixer, frame));
+            }
+            out.push(apply_prefix(&mut self.prefixer, frame));
+            self.after = self.context;
+            self.printed = true;
+            self.skipped = false;
+        } else if self.after > 0 {
+            self.after -= 1;
+            out.push(apply_prefix(&mut self.prefixer, frame));
+        } else {
+            self.before.push_back(frame);
+            if self.before.len() > self.context {
+                self.before.pop_front();
+                self.skipped = true;
+            }
+        }
+        out
+    }
+}
+
+/// CPU usage as a percentage of the host, computed from the delta between this sample and the
+/// previous one the same way the Docker CLI does.
+fn cpu_percent(stats: &Stats) -> f64 {
+    let cpu_delta = stats.cpu_stats.cpu_usage.total_usage as f64
+        - stats.precpu_stats.cpu_usage.total_usage as f64;
+    let system_delta = stats.cpu_stats.system_cpu_usage.unwrap_or(0) as f64
+        - stats.precpu_stats.system_cpu_usage.unwrap_or(0) as f64;
+    let online_cpus = stats
+        .cpu_stats
+        .online_cpus
+        .or_else(|| {
+            stats
+                .cpu_stats
+                .cpu_usage
+                .percpu_usage
+                .as_ref()
+                .map(|percpu| percpu.len() as u64)
+        })
+        .unwrap_or(1) as f64;
+
+    if cpu_delta > 0.0 && system_delta > 0.0 {
+        cpu_delta / system_delta * online_cpus * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Memory in use minus the inactive page cache, which is what `docker stats` reports.
+fn memory_usage(memory: &MemoryStats) -> u64 {
+    let cache = match memory.stats {
+        Some(MemoryStatsStats::V1(v1)) => v1.total_inactive_file,
+        Some(MemoryStatsStats::V2(v2)) => v2.inactive_file,
+        None => 0,
+    };
+    memory.usage.unwrap_or(0).saturating_sub(cache)
+}
+
+/// Prints a resource usage table with a row per stats sample, and a TOTAL row when there's more
+/// than one.
+fn print_stats_table<'a>(
+    stats: impl Iterator<Item = &'a Stats>,
+    columns: &[StatsColumn],
+    output: &OutputArgs,
+) {
+    let columns = match columns {
+        [] => StatsColumn::value_variants(),
+        columns => columns,
+    };
+
+    let mut header = vec![Cell::new("Container").style_spec("b")];
+    for column in columns {
+        let titles: &[&str] = match column {
+            StatsColumn::Cpu => &["CPU %"],
+            StatsColumn::Mem => &["Mem Usage / Limit", "Mem %"],
+            StatsColumn::Net => &["Net I/O"],
+            StatsColumn::Block => &["Block I/O"],
+            StatsColumn::Pids => &["PIDs"],
+        };
+        header.extend(titles.iter().map(|title| Cell::new(title).style_spec("b")));
+    }
+
+    let mut stats_table = output.table(Row::new(header));
+
+    let mut total = Usage::default();
+    let mut rows = 0;
+    for stats in stats {
+        let usage = Usage::of(stats);
+        let name = stats.name.strip_prefix('/').unwrap_or(&stats.name);
+        stats_table.add_row(usage.row(name, columns, output));
+        total.add(&usage);
+        rows += 1;
+    }
+    if rows > 1 {
+        stats_table.add_row(total.row("TOTAL", columns, output));
+    }
+
+    stats_table.printstd();
+}
+
+/// The figures of one `ps stats` row.
+#[derive(Debug, Default)]
+struct Usage {
+    cpu_percent: f64,
+    memory: u64,
+    memory_limit: u64,
+    memory_percent: f64,
+    rx: u64,
+    tx: u64,
+    read: u64,
+    write: u64,
+    pids: u64,
+}
+
+impl Usage {
+    fn of(stats: &Stats) -> Usage {
+        let memory = memory_usage(&stats.memory_stats);
+        let memory_limit = stats.memory_stats.limit.unwrap_or(0);
+        let memory_percent = if memory_limit > 0 {
+            memory as f64 / memory_limit as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        let (rx, tx) = stats
+            .networks
+            .iter()
+            .flat_map(|networks| networks.values())
+            .fold((0, 0), |(rx, tx), net| {
+                (rx + net.rx_bytes, tx + net.tx_bytes)
+            });
+
+        let (read, write) = stats
+            .blkio_stats
+            .io_service_bytes_recursive
+            .iter()
+            .flatten()
+            .fold((0, 0), |(read, write), entry| {
+                match entry.op.to_ascii_lowercase().as_str() {
+                    "read" => (read + entry.value, write),
+                    "write" => (read, write + entry.value),
+                    _ => (read, write),
+                }
+            });
+
+        Usage {
+            cpu_percent: cpu_percent(stats),
+            memory,
+            memory_limit,
+            memory_percent,
+            rx,
+            tx,
+            read,
+            write,
+            pids: stats.pids_stats.current.unwrap_or(0),
+        }
+    }
+
+    /// Adds another container's figures to a running total. The memory limit is the host's
+    /// for containers without one of their own, so the largest is kept rather than a sum.
+    fn add(&mut self, other: &Usage) {
+        self.cpu_percent += other.cpu_percent;
+        self.memory += other.memory;
+        self.memory_limit = self.memory_limit.max(other.memory_limit);
+        self.memory_percent += other.memory_percent;
+        self.rx += other.rx;
+        self.tx += other.tx;
+        self.read += other.read;
+        self.write += other.write;
+        self.pids += other.pids;
+    }
+
+    fn row(&self, name: &str, columns: &[StatsColumn], output: &OutputArgs) -> Row {
+        let pair =
+            |a: u64, b: u64| format!("{} / {}", output.size(a as i64), output.size(b as i64));
+
+        let mut cells = vec![Cell::new(name)];
+        for column in columns {
+            match column {
+                StatsColumn::Cpu => cells.push(Cell::new(&format!("{:.2}%", self.cpu_percent))),
+                StatsColumn::Mem => {
+                    cells.push(Cell::new(&pair(self.memory, self.memory_limit)));
+                    cells.push(Cell::new(&format!("{:.2}%", self.memory_percent)));
+                }
+                StatsColumn::Net => cells.push(Cell::new(&pair(self.rx, self.tx))),
+                StatsColumn::Block => cells.push(Cell::new(&pair(self.read, self.write))),
+                StatsColumn::Pids => cells.push(Cell::new(&self.pids.to_string())),
+            }
+        }
+        Row::new(cells)
+    }
+}
+
+/// Resolves a container name, full ID or unique ID prefix (such as the 12-character short ID)
+/// to the container's full ID.
+///
+/// Exact IDs and names win over prefixes, so a container named like another's ID prefix is
+/// still reachable by name.
+#[tracing::instrument(skip(docker, retry))]
+async fn resolve_container(
+    docker: &Docker,
+    retry: Retry,
+    target: &str,
+) -> Result<String, CliError> {
+    ContainerResolver::new(docker, retry).await?.resolve(target)
+}
+
+/// Strips the `/` the daemon puts in front of names, refusing targets that are empty once
+/// trimmed: as a prefix they would match every container.
+fn check_target(target: &str) -> Result<&str, CliError> {
+    let target = target.strip_prefix('/').unwrap_or(target);
+    if target.trim().is_empty() {
+        return Err(CliError::EmptyTarget);
+    }
+    Ok(target)
+}
+
+/// Resolves the targets of a command against one listing of all containers, so commands
+/// taking many targets list them once rather than once per target.
+struct ContainerResolver {
+    containers: Vec<ContainerSummary>,
+}
+
+impl ContainerResolver {
+    async fn new(docker: &Docker, retry: Retry) -> Result<ContainerResolver, CliError> {
+        let options = ListContainersOptions::<String> {
+            all: true,
+            ..Default::default()
+        };
+        let containers = retry
+            .run("list containers", || {
+                docker.list_containers(Some(options.clone()))
+            })
+            .await?;
+        Ok(ContainerResolver { containers })
+    }
+
+    /// See [`resolve_container`].
+    fn resolve(&self, target: &str) -> Result<String, CliError> {
+        let target = check_target(target)?;
+        let containers = &self.containers;
+
+        let exact = containers.iter().find(|container| {
+            container.id.as_deref() == Some(target)
+                || container
+                    .names
+                    .iter()
+                    .flatten()
+                    .any(|name| name.strip_prefix('/').unwrap_or(name) == target)
+        });
+        if let Some(id) = exact.and_then(|container| container.id.clone()) {
+            tracing::debug!(id, "matched by exact name or ID");
+            return Ok(id);
+        }
+
+        let matches: Vec<&ContainerSummary> = containers
+            .iter()
+            .filter(|container| {
+                container
+                    .id
+                    .as_deref()
+                    .is_some_and(|id| id.starts_with(target))
+            })
+            .collect();
+
+        match matches.as_slice() {
+            [] => Err(CliError::NoSuchContainer(target.to_string())),
+            [container] => Ok(container.id.clone().unwrap_or_default()),
+            _ => Err(CliError::AmbiguousContainer {
+                target: target.to_string(),
+                candidates: matches
+                    .iter()
+                    .map(|container| {
+                        format!(
+                            "{} ({})",
+                            short_id(container.id.as_deref().unwrap_or("")),
+                            container_name(container)
+                        )
+                    })
+                    .collect(),
+            }),
+        }
+    }
+}
+
+/// Starts a container, returning `false` when it was already running.
+///
+/// The daemon answers a start on a running container with `304 Not Modified`, which bollard
+/// reports as success, so the state is checked up front.
+#[tracing::instrument(skip(docker))]
+async fn start(docker: &Docker, container_name: &str) -> Result<bool, bollard::errors::Error> {
+    let running = docker
+        .inspect_container(container_name, None::<InspectContainerOptions>)
+        .await?
+        .state
+        .and_then(|state| state.running)
+        .unwrap_or(false);
+
+    if running {
+        return Ok(false);
+    }
+
+    docker
+        .start_container(container_name, None::<StartContainerOptions<String>>)
+        .await?;
+    Ok(true)
+}
+
+/// Inspects one container of the listing, for `ps info --inspect` and `--long`. The size, which
+/// fills the inspect table's Container Size column, is slow to work out and only asked for with
+/// `size`.
+async fn conc(
+    docker: &Docker,
+    retry: Retry,
+    container: &ContainerSummary,
+    size: bool,
+) -> Result<ContainerInspectResponse, CliError> {
+    let container_id = container.id.as_deref().ok_or_else(|| {
+        CliError::UnexpectedResponse("container summary without an ID".to_string())
+    })?;
+
+    let options = InspectContainerOptions { size };
+    Ok(retry
+        .run("inspect container", || {
+            docker.inspect_container(container_id, Some(options))
+        })
+        .await?)
+}
+
+/// Prints the ID/name/image/size/state summary table of an inspected container.
+fn print_inspect_table(stats: ContainerInspectResponse, output: &OutputArgs) {
+    let ContainerInspectResponse {
+        id,
+        name,
+        image,
+        size_root_fs,
+        state,
+        network_settings,
+        ..
+    } = stats;
+
+    // println!("[#] Container name  {:?}", name);
+
+    // Create the table
+    let mut stats_table = output
+        .table(row![b->"ID", b->"Container Name", b->"Image ID", b->"Container Size", b->"State", b->"Ports",]);
+
+    let stats_row = Row::new(vec![
+        Cell::new(id.as_deref().unwrap_or("")),
+        Cell::new(name.as_deref().unwrap_or("")),
+        Cell::new(image.as_deref().unwrap_or("")),
+        Cell::new(
+            &size_root_fs
+                .map(|s| output.size(s))
+                .unwrap_or_else(|| String::from("-")),
+        ),
+        Cell::new(
+            state
+                .and_then(|state| state.status)
+                .as_ref()
+                .map_or("", |st| st.as_ref()),
+        ),
+        Cell::new(&format_port_bindings(
+            &network_settings
+                .and_then(|settings| settings.ports)
+                .unwrap_or_default(),
+        )),
+    ]);
+    stats_table.add_row(stats_row);
+
+    // Print the table to stdout
+    stats_table.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The output options `args` would give, e.g. `["--no-trunc"]`.
+    fn output_args(args: &[&str]) -> OutputArgs {
+        let args = ["locci"].iter().chain(args).chain(&["img", "list"]);
+        Cli::try_parse_from(args).unwrap().output
+    }
+
+    fn image(id: &str, repo_tags: &[&str]) -> ImageSummary {
+        ImageSummary {
+            id: id.to_string(),
+            repo_tags: repo_tags.iter().map(|tag| tag.to_string()).collect(),
+            created: 1_700_000_000,
+            size: 5_000_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn display_repo_tag_renders_dangling_images_as_none() {
+        assert_eq!(display_repo_tag(&[], false), "<none>");
+        assert_eq!(
+            display_repo_tag(&["<none>:<none>".to_string()], false),
+            "<none>"
+        );
+        assert_eq!(
+            display_repo_tag(&["<none>:<none>".to_string()], true),
+            "<none>"
+        );
+    }
+
+    #[test]
+    fn display_repo_tag_lists_every_tag_when_wide() {
+        let tags = ["nginx:1.27".to_string(), "nginx:latest".to_string()];
+        assert_eq!(display_repo_tag(&tags, false), "nginx:1.27");
+        assert_eq!(display_repo_tag(&tags, true), "nginx:1.27, nginx:latest");
+    }
+
+    #[test]
+    fn image_rows_include_dangling_images() {
+        let images = [
+            image("sha256:aaaaaaaaaaaaaaaaaaaa", &["nginx:latest"]),
+            image("sha256:bbbbbbbbbbbbbbbbbbbb", &[]),
+            image("sha256:cccccccccccccccccccc", &["<none>:<none>"]),
+        ];
+        let rows = image_rows(&images, &output_args(&["--bytes"]), true);
+
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| row.iter().map(Cell::get_content).collect())
+            .collect();
+        assert_eq!(cells.len(), 3);
+        assert_eq!(cells[0][..2], ["aaaaaaaaaaaa", "nginx:latest"]);
+        assert_eq!(cells[1][..3], ["bbbbbbbbbbbb", "<none>", "<none>"]);
+        assert_eq!(cells[2][1], "<none>");
+        assert_eq!(cells[2][4], "5000000");
+    }
+
+    fn container(id: &str, name: &str) -> ContainerSummary {
+        ContainerSummary {
+            id: Some(id.to_string()),
+            names: Some(vec![format!("/{name}")]),
+            ..Default::default()
+        }
+    }
+
+    fn resolver() -> ContainerResolver {
+        ContainerResolver {
+            containers: vec![
+                container("4f66ad9a0b2e11112222", "web"),
+                container("4f77bbbb0000aaaa1111", "db"),
+                container("9c0ffee00000aaaa1111", "4f66"),
+            ],
+        }
+    }
+
+    #[test]
+    fn resolve_matches_names_ids_and_prefixes() {
+        let containers = resolver();
+        assert_eq!(containers.resolve("web").unwrap(), "4f66ad9a0b2e11112222");
+        assert_eq!(containers.resolve("/db").unwrap(), "4f77bbbb0000aaaa1111");
+        assert_eq!(containers.resolve("4f77").unwrap(), "4f77bbbb0000aaaa1111");
+        // A name that looks like an ID prefix wins over the prefix
+        assert_eq!(containers.resolve("4f66").unwrap(), "9c0ffee00000aaaa1111");
+        assert!(matches!(
+            containers.resolve("4f"),
+            Err(CliError::AmbiguousContainer { .. })
+        ));
+        assert!(matches!(
+            containers.resolve("nope"),
+            Err(CliError::NoSuchContainer(_))
+        ));
+    }
+
+    #[test]
+    fn resolve_refuses_empty_targets() {
+        let containers = ContainerResolver {
+            containers: vec![container("4f66ad9a0b2e11112222", "web")],
+        };
+        for target in ["", " ", "/", "\t"] {
+            assert!(matches!(
+                containers.resolve(target),
+                Err(CliError::EmptyTarget)
+            ));
+        }
+    }
+
+    #[test]
+    fn short_id_cuts_full_ids_to_twelve_characters() {
+        let id = "4f66ad9a0b2e4c7d8e9f00112233445566778899aabbccddeeff001122334455";
+        assert_eq!(id.len(), 64);
+        assert_eq!(short_id(id), "4f66ad9a0b2e");
+    }
+
+    #[test]
+    fn short_id_leaves_short_ids_alone() {
+        assert_eq!(short_id("4f66ad9a"), "4f66ad9a");
+        assert_eq!(short_id("4f66ad9a0b2e"), "4f66ad9a0b2e");
+        assert_eq!(short_id(""), "");
+    }
+
+    #[test]
+    fn short_id_drops_the_digest_algorithm() {
+        let id = "sha256:4f66ad9a0b2e4c7d8e9f00112233445566778899aabbccddeeff001122334455";
+        assert_eq!(short_id(id), "4f66ad9a0b2e");
+        assert_eq!(short_id("sha256:abc"), "abc");
+    }
+
+    #[test]
+    fn short_id_counts_characters_not_bytes() {
+        assert_eq!(short_id("ééééééééééééé"), "éééééééééééé");
+    }
+
+    fn stdout(message: &'static str) -> LogOutput {
+        LogOutput::StdOut {
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn log_grep_matches_before_the_name_prefix() {
+        let inspect = ContainerInspectResponse {
+            name: Some("/web".to_string()),
+            ..Default::default()
+        };
+        let prefixer = LogPrefixer::new(&inspect, Some(LogPrefix::Name), false, Some(36));
+        let mut grep = LogGrep::new(Regex::new("^ERROR").unwrap(), 0, Some(prefixer));
+
+        let printed: Vec<Vec<u8>> = ["ERROR disk full\n", "web is up\n", "ERROR again\n"]
+            .into_iter()
+            .flat_map(|line| grep.take(stdout(line)))
+            .map(|frame| frame.into_bytes().to_vec())
+            .collect();
+        assert_eq!(
+            printed,
+            [
+                b"\x1B[36m[web]\x1B[0m ERROR disk full\n".to_vec(),
+                b"\x1B[36m[web]\x1B[0m ERROR again\n".to_vec(),
+            ]
+        );
+    }
 }
@@ -0,0 +1,201 @@
+//! Working with Docker Networks
+
+use clap::{Args, Subcommand};
+use std::default::Default;
+
+use bollard::models::{Ipam, IpamConfig, Network as DockerNetwork};
+use bollard::network::{CreateNetworkOptions, InspectNetworkOptions, ListNetworksOptions};
+use bollard::Docker;
+
+use prettytable::{row, Cell, Row};
+
+use crate::error::{CliError, Failures};
+use crate::{print_json, print_json_list, OutputArgs};
+
+#[derive(Debug, Args)]
+pub struct Network {
+    #[clap(subcommand)]
+    pub command: NetworkOptions,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum NetworkOptions {
+    /// List All Networks
+    List,
+    /// Show Detailed Information On A Network
+    Inspect(InspectNetworkInfo),
+    /// Create A Network
+    Create(CreateNetworkInfo),
+    /// Remove One Or More Networks
+    Rm(RemoveNetworkInfo),
+}
+
+#[derive(Debug, Args)]
+pub struct InspectNetworkInfo {
+    /// Name or ID of the Network
+    pub network_name: String,
+
+    /// Print the raw inspect response as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CreateNetworkInfo {
+    /// Name of the Network
+    pub network_name: String,
+
+    /// Network driver to use
+    #[arg(long, default_value = "bridge")]
+    pub driver: String,
+
+    /// Subnet in CIDR format (e.g. 172.28.0.0/16)
+    #[arg(long)]
+    pub subnet: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct RemoveNetworkInfo {
+    /// Names or IDs of the Networks to remove
+    #[arg(required = true)]
+    pub network_names: Vec<String>,
+}
+
+pub async fn run(
+    docker: &Docker,
+    command: &NetworkOptions,
+    output: &OutputArgs,
+) -> Result<(), CliError> {
+    match command {
+        // ./exe network list
+        NetworkOptions::List => {
+            let networks = docker
+                .list_networks(None::<ListNetworksOptions<String>>)
+                .await?;
+
+            if output.is_json() {
+                return print_json_list(output, &networks);
+            }
+
+            // Network Summary table
+            let mut network_summary_table =
+                output.table(row![b->"ID", b->"Network Name", b->"Driver", b->"Scope"]);
+
+            for DockerNetwork {
+                id,
+                name,
+                driver,
+                scope,
+                ..
+            } in networks.iter()
+            {
+                let id = id.as_deref().unwrap_or("");
+                let network_summary_row = Row::new(vec![
+                    Cell::new(output.id(id)),
+                    Cell::new(name.as_deref().unwrap_or("")),
+                    Cell::new(driver.as_deref().unwrap_or("")),
+                    Cell::new(scope.as_deref().unwrap_or("")),
+                ]);
+
+                network_summary_table.add_row(network_summary_row);
+            }
+
+            network_summary_table.printstd();
+            Ok(())
+        }
+        // ./exe network inspect [--json] <network_name>
+        NetworkOptions::Inspect(InspectNetworkInfo { network_name, json }) => {
+            let network = docker
+                .inspect_network(network_name, None::<InspectNetworkOptions<String>>)
+                .await?;
+
+            if *json || output.is_json() {
+                return print_json(output, &network);
+            }
+
+            let DockerNetwork {
+                id,
+                name,
+                driver,
+                scope,
+                ipam,
+                containers,
+                ..
+            } = network;
+
+            let subnets = ipam
+                .and_then(|Ipam { config, .. }| config)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|IpamConfig { subnet, .. }| subnet)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            // Network Inspect table
+            let mut network_inspect_table = output.table(
+                row![b->"ID", b->"Network Name", b->"Driver", b->"Scope", b->"Subnets", b->"Containers"],
+            );
+
+            network_inspect_table.add_row(Row::new(vec![
+                Cell::new(id.as_deref().unwrap_or("")),
+                Cell::new(name.as_deref().unwrap_or("")),
+                Cell::new(driver.as_deref().unwrap_or("")),
+                Cell::new(scope.as_deref().unwrap_or("")),
+                Cell::new(&subnets),
+                Cell::new(&containers.map_or(0, |c| c.len()).to_string()),
+            ]));
+
+            network_inspect_table.printstd();
+            Ok(())
+        }
+        // ./exe network create [--driver <driver>] [--subnet <cidr>] <network_name>
+        NetworkOptions::Create(CreateNetworkInfo {
+            network_name,
+            driver,
+            subnet,
+        }) => {
+            let ipam = Ipam {
+                config: subnet.as_ref().map(|subnet| {
+                    vec![IpamConfig {
+                        subnet: Some(subnet.clone()),
+                        ..Default::default()
+                    }]
+                }),
+                ..Default::default()
+            };
+
+            let options = CreateNetworkOptions {
+                name: network_name.as_str(),
+                driver: driver.as_str(),
+                check_duplicate: true,
+                ipam,
+                ..Default::default()
+            };
+
+            let created = docker.create_network(options).await?;
+            if let Some(warning) = created.warning.filter(|w| !w.is_empty()) {
+                eprintln!("Warning: {warning}");
+            }
+            Ok(println!(
+                "Created Network: {network_name:?} ({})",
+                created.id.unwrap_or_default()
+            ))
+        }
+        // ./exe network rm <network_name>...
+        NetworkOptions::Rm(RemoveNetworkInfo { network_names }) => {
+            let mut failed = Failures::default();
+            for network_name in network_names {
+                match docker.remove_network(network_name).await {
+                    Ok(_) => println!("Removed Network: {network_name:?}"),
+                    Err(e) => {
+                        eprintln!("Failed to remove network {network_name:?}: {e}");
+                        failed.record(e);
+                    }
+                }
+            }
+
+            failed.finish("network", "be removed")?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Progress of image pulls, pushes and builds
+//!
+//! On a terminal every layer gets its own bar, filled in from the `progressDetail` the daemon
+//! sends; piped, the daemon's status lines are printed one per update the way they always were.
+
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+use bollard::models::ProgressDetail;
+
+/// Statuses after which a layer has nothing more to report.
+const DONE: [&str; 5] = [
+    "Pull complete",
+    "Already exists",
+    "Pushed",
+    "Layer already exists",
+    "Mounted from",
+];
+
+pub struct Progress {
+    /// `None` when stdout isn't a terminal
+    bars: Option<MultiProgress>,
+    /// Keyed by layer ID, with updates that carry none (push progress) sharing the `""` bar
+    layers: HashMap<String, ProgressBar>,
+}
+
+impl Progress {
+    pub fn new() -> Progress {
+        let bars = std::io::stdout()
+            .is_terminal()
+            .then(|| MultiProgress::with_draw_target(ProgressDrawTarget::stdout()));
+        Progress {
+            bars,
+            layers: HashMap::new(),
+        }
+    }
+
+    /// Shows one progress update, of layer `id` when the daemon named one.
+    pub fn update(
+        &mut self,
+        id: Option<&str>,
+        status: &str,
+        progress: Option<&str>,
+        detail: Option<&ProgressDetail>,
+    ) {
+        let Some(bars) = &self.bars else {
+            let line = match id {
+                Some(id) => format!("{id}: {status} {}", progress.unwrap_or_default()),
+                None => format!("{status} {}", progress.unwrap_or_default()),
+            };
+            return println!("{}", line.trim_end());
+        };
+
+        let total = detail
+            .and_then(|detail| detail.total)
+            .filter(|total| *total > 0);
+        let key = match (id, total) {
+            (Some(id), _) => id,
+            (None, Some(_)) => "",
+            // Image-wide messages like "Digest: sha256:..." go above the bars
+            (None, None) => return bars.println(status).unwrap_or_default(),
+        };
+
+        let bar = self.layers.entry(key.to_string()).or_insert_with(|| {
+            let bar = bars.add(ProgressBar::new(0));
+            bar.set_style(Self::style(false));
+            bar.set_prefix(key.to_string());
+            bar
+        });
+
+        match total {
+            Some(total) => {
+                if bar.length() != Some(total as u64) {
+                    bar.set_style(Self::style(true));
+                    bar.set_length(total as u64);
+                }
+                let current = detail.and_then(|detail| detail.current).unwrap_or_default();
+                bar.set_position(current.max(0) as u64);
+            }
+            // "Waiting", "Verifying Checksum" and the like have nothing to measure
+            None => bar.set_style(Self::style(false)),
+        }
+        bar.set_message(status.to_string());
+
+        if DONE.iter().any(|done| status.starts_with(done)) {
+            bar.finish();
+        }
+    }
+
+    /// Prints a line of build output without tearing through the bars.
+    pub fn println(&self, line: &str) {
+        match &self.bars {
+            Some(bars) => bars.println(line.trim_end()).unwrap_or_default(),
+            None => print!("{line}"),
+        }
+    }
+
+    fn style(measured: bool) -> ProgressStyle {
+        let template = if measured {
+            "{prefix:12} {msg:<20} [{bar:30}] {bytes}/{total_bytes}"
+        } else {
+            "{prefix:12} {msg}"
+        };
+        ProgressStyle::with_template(template)
+            .expect("progress templates are valid")
+            .progress_chars("=> ")
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        // Whatever didn't finish (an error cut the stream short) is left where it stopped
+        for bar in self.layers.values() {
+            bar.abandon();
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Registry credentials, as stored by `docker login`
+
+use std::collections::HashMap;
+
+use base64::Engine;
+use serde::Deserialize;
+
+use bollard::auth::DockerCredentials;
+
+use crate::context::docker_config_dir;
+use crate::error::CliError;
+
+/// The key `docker login` stores Docker Hub credentials under.
+const DOCKER_HUB: &str = "https://index.docker.io/v1/";
+
+/// The parts of `~/.docker/config.json` that hold registry logins.
+#[derive(Debug, Default, Deserialize)]
+struct DockerConfig {
+    #[serde(default)]
+    auths: HashMap<String, AuthEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AuthEntry {
+    /// base64 of `username:password`
+    auth: Option<String>,
+    identitytoken: Option<String>,
+}
+
+/// The registry an image reference points at, `docker.io` references giving Docker Hub.
+///
+/// Like the Docker CLI, the first path component only counts as a registry host when it has a
+/// dot or a port in it, or is `localhost`.
+pub fn registry_host(image: &str) -> &str {
+    match image.split_once('/') {
+        Some((host, _)) if host.contains(['.', ':']) || host == "localhost" => match host {
+            "docker.io" | "index.docker.io" | "registry-1.docker.io" => DOCKER_HUB,
+            host => host,
+        },
+        _ => DOCKER_HUB,
+    }
+}
+
+/// Picks the credentials for `registry`: explicit `--username`/`--password` win, otherwise
+/// whatever `docker login` saved in `~/.docker/config.json`. `None` means push anonymously.
+pub fn credentials(
+    registry: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> Result<Option<DockerCredentials>, CliError> {
+    if let Some(username) = username {
+        return Ok(Some(DockerCredentials {
+            username: Some(username.to_string()),
+            password: password.map(str::to_string),
+            serveraddress: Some(registry.to_string()),
+            ..Default::default()
+        }));
+    }
+
+    let path = docker_config_dir().join("config.json");
+    let config: DockerConfig = match std::fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    // Logins are saved under the bare host or a full URL, depending on the client version
+    let entry = config.auths.iter().find_map(|(key, entry)| {
+        let host = key
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/');
+        let wanted = registry
+            .trim_start_matches("https://")
+            .trim_end_matches('/');
+        (key == registry || host == wanted).then_some(entry)
+    });
+    let Some(AuthEntry {
+        auth,
+        identitytoken,
+    }) = entry
+    else {
+        return Ok(None);
+    };
+
+    let (username, password) = match auth {
+        Some(auth) => {
+            let decoded = base64::engine::general_purpose::STANDARD
+                .decode(auth.trim())
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .ok_or_else(|| CliError::Auth {
+                    registry: registry.to_string(),
+                    message: format!("the saved login in {} is corrupt", path.display()),
+                })?;
+            match decoded.split_once(':') {
+                Some((username, password)) => {
+                    (Some(username.to_string()), Some(password.to_string()))
+                }
+                None => (Some(decoded), None),
+            }
+        }
+        None => (None, None),
+    };
+
+    Ok(Some(DockerCredentials {
+        username,
+        password,
+        identitytoken: identitytoken.clone(),
+        serveraddress: Some(registry.to_string()),
+        ..Default::default()
+    }))
+}
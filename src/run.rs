@@ -0,0 +1,186 @@
+//! Creating and starting a container in one go
+
+use clap::Args;
+use std::collections::HashMap;
+use std::default::Default;
+
+use futures_util::stream::StreamExt;
+
+use bollard::container::{
+    Config, CreateContainerOptions, LogsOptions, StartContainerOptions, WaitContainerOptions,
+};
+use bollard::models::{HostConfig, PortBinding};
+use bollard::Docker;
+
+use crate::error::CliError;
+use crate::{parse_platform, until_interrupted, write_log_frame};
+
+#[derive(Debug, Args)]
+pub struct Run {
+    /// Image to create the container from (e.g. nginx:latest)
+    pub image: String,
+
+    /// Name of the container, generated by the daemon when omitted
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Set an environment variable (KEY=VALUE), may be repeated
+    #[arg(short, long = "env", value_name = "KEY=VALUE")]
+    pub env: Vec<String>,
+
+    /// Publish a container port on the host (HOST:CONTAINER[/PROTO]), may be repeated
+    #[arg(short, long = "publish", value_name = "HOST:CONTAINER", value_parser = parse_port)]
+    pub publish: Vec<PortMapping>,
+
+    /// Bind mount a host path or volume (SRC:DST[:ro]), may be repeated
+    #[arg(short, long = "volume", value_name = "SRC:DST")]
+    pub volumes: Vec<String>,
+
+    /// Run in the background and only print the container ID
+    #[arg(short, long)]
+    pub detach: bool,
+
+    /// Platform of the image to run, for images built for several (os/arch[/variant], e.g.
+    /// linux/arm64)
+    #[arg(long, value_parser = parse_platform)]
+    pub platform: Option<String>,
+
+    /// Command to run instead of the image's default, passed after `--`
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub command: Vec<String>,
+}
+
+/// A `-p` port mapping, with the container port in the daemon's `port/proto` form.
+#[derive(Debug, Clone)]
+pub struct PortMapping {
+    pub host_ip: Option<String>,
+    pub host_port: String,
+    pub container_port: String,
+}
+
+/// Parses `HOST:CONTAINER[/PROTO]` or `IP:HOST:CONTAINER[/PROTO]`, defaulting the protocol to tcp.
+pub fn parse_port(mapping: &str) -> Result<PortMapping, String> {
+    let (host, container) = mapping
+        .rsplit_once(':')
+        .ok_or_else(|| format!("expected HOST:CONTAINER, got {mapping:?}"))?;
+    let (host_ip, host_port) = match host.rsplit_once(':') {
+        Some((ip, port)) => (Some(ip.to_string()), port),
+        None => (None, host),
+    };
+
+    let (port, proto) = container.split_once('/').unwrap_or((container, "tcp"));
+    if port.parse::<u16>().is_err() || !(host_port.is_empty() || host_port.parse::<u16>().is_ok()) {
+        return Err(format!("invalid port mapping {mapping:?}"));
+    }
+
+    Ok(PortMapping {
+        host_ip,
+        host_port: host_port.to_string(),
+        container_port: format!("{port}/{proto}"),
+    })
+}
+
+/// Groups `-p` mappings by container port, the shape `HostConfig.PortBindings` takes.
+pub fn port_bindings(publish: &[PortMapping]) -> HashMap<String, Option<Vec<PortBinding>>> {
+    let mut port_bindings: HashMap<String, Option<Vec<PortBinding>>> = HashMap::new();
+    for PortMapping {
+        host_ip,
+        host_port,
+        container_port,
+    } in publish
+    {
+        port_bindings
+            .entry(container_port.clone())
+            .or_insert_with(|| Some(Vec::new()))
+            .get_or_insert_with(Vec::new)
+            .push(PortBinding {
+                host_ip: host_ip.clone(),
+                host_port: Some(host_port.clone()),
+            });
+    }
+    port_bindings
+}
+
+/// The container ports of `-p` mappings, which have to be exposed for the bindings to apply.
+pub fn exposed_ports(publish: &[PortMapping]) -> HashMap<String, HashMap<(), ()>> {
+    publish
+        .iter()
+        .map(|mapping| (mapping.container_port.clone(), HashMap::new()))
+        .collect()
+}
+
+// ./exe run [--name <name>] [--platform <os/arch>] [-e KEY=VALUE]... [-p HOST:CONTAINER]... [-v SRC:DST]... [-d] <image> [-- <command>...]
+pub async fn run(docker: &Docker, run: &Run) -> Result<(), CliError> {
+    let Run {
+        image,
+        name,
+        env,
+        publish,
+        volumes,
+        detach,
+        platform,
+        command,
+    } = run;
+
+    let port_bindings = port_bindings(publish);
+    let host_config = HostConfig {
+        binds: (!volumes.is_empty()).then(|| volumes.clone()),
+        port_bindings: (!port_bindings.is_empty()).then_some(port_bindings),
+        ..Default::default()
+    };
+
+    let config = Config {
+        image: Some(image.clone()),
+        env: (!env.is_empty()).then(|| env.clone()),
+        cmd: (!command.is_empty()).then(|| command.clone()),
+        exposed_ports: (!publish.is_empty()).then(|| exposed_ports(publish)),
+        attach_stdout: Some(!*detach),
+        attach_stderr: Some(!*detach),
+        host_config: Some(host_config),
+        ..Default::default()
+    };
+
+    // An empty name has the daemon generate one, as when no options are sent at all
+    let options = (name.is_some() || platform.is_some()).then(|| CreateContainerOptions {
+        name: name.as_deref().unwrap_or_default(),
+        platform: platform.as_deref(),
+    });
+    let created = docker.create_container(options, config).await?;
+    for warning in created.warnings.iter().filter(|w| !w.is_empty()) {
+        eprintln!("Warning: {warning}");
+    }
+
+    docker
+        .start_container(&created.id, None::<StartContainerOptions<String>>)
+        .await?;
+
+    if *detach {
+        return Ok(println!("{}", created.id));
+    }
+    eprintln!("Started Container: {}", created.id);
+
+    // Stream the output until the container exits, then hand its exit status on
+    let options = LogsOptions::<String> {
+        stdout: true,
+        stderr: true,
+        follow: true,
+        ..Default::default()
+    };
+    let log_stream = docker.logs(&created.id, Some(options));
+    if until_interrupted(log_stream, |frame| Ok(write_log_frame(frame)?)).await? {
+        eprintln!("Detached, container {} is still running", created.id);
+        return Ok(());
+    }
+
+    let mut wait_stream = docker.wait_container(&created.id, None::<WaitContainerOptions<String>>);
+    while let Some(exit) = wait_stream.next().await {
+        match exit {
+            Ok(_) => {}
+            Err(bollard::errors::Error::DockerContainerWaitError { code, .. }) => {
+                return Err(CliError::ExitStatus(u8::try_from(code).unwrap_or(1)));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,313 @@
+//! Daemon-wide information and housekeeping
+
+use clap::{Args, Subcommand};
+
+use bollard::container::PruneContainersOptions;
+use bollard::image::PruneImagesOptions;
+use bollard::models::{ImageDeleteResponseItem, SystemDataUsageResponse, SystemInfo};
+use bollard::network::PruneNetworksOptions;
+use bollard::system::Version;
+use bollard::Docker;
+
+use prettytable::{row, Cell, Row, Table};
+
+use crate::error::CliError;
+use crate::{confirm, print_json, OutputArgs};
+
+#[derive(Debug, Args)]
+pub struct System {
+    #[clap(subcommand)]
+    pub command: SystemOptions,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SystemOptions {
+    /// Show Docker Disk Usage
+    Df,
+    /// Remove Stopped Containers, Dangling Images And Unused Networks
+    Prune(PruneInfo),
+    /// Show Daemon-Wide Information
+    Info(SystemJsonInfo),
+    /// Show The Docker Engine And API Versions
+    Version(SystemJsonInfo),
+}
+
+#[derive(Debug, Args)]
+pub struct SystemJsonInfo {
+    /// Print the raw response as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct PruneInfo {
+    /// Do not prompt for confirmation
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+pub async fn run(
+    docker: &Docker,
+    command: &SystemOptions,
+    output: &OutputArgs,
+) -> Result<(), CliError> {
+    match command {
+        // ./exe system df
+        SystemOptions::Df => {
+            let usage = docker.df().await?;
+
+            if output.is_json() {
+                return print_json(output, &usage);
+            }
+
+            // Disk Usage table
+            let mut disk_usage_table =
+                output.table(row![b->"Type", b->"Total", b->"Active", b->"Size", b->"Reclaimable"]);
+
+            for DiskUsage {
+                kind,
+                total,
+                active,
+                size,
+                reclaimable,
+            } in disk_usage(&usage)
+            {
+                let reclaimable_percent = if size > 0 {
+                    reclaimable * 100 / size
+                } else {
+                    0
+                };
+
+                disk_usage_table.add_row(Row::new(vec![
+                    Cell::new(kind),
+                    Cell::new(&total.to_string()),
+                    Cell::new(&active.to_string()),
+                    Cell::new(&output.size(size)),
+                    Cell::new(&format!(
+                        "{} ({reclaimable_percent}%)",
+                        output.size(reclaimable)
+                    )),
+                ]));
+            }
+
+            disk_usage_table.printstd();
+            Ok(())
+        }
+        // ./exe system prune [-f]
+        SystemOptions::Prune(PruneInfo { force }) => {
+            if !*force
+                && !confirm(
+                    "This will remove all stopped containers, dangling images and unused networks.",
+                )?
+            {
+                return Ok(println!("Prune cancelled"));
+            }
+
+            let containers = docker
+                .prune_containers(None::<PruneContainersOptions<String>>)
+                .await?;
+            for id in containers.containers_deleted.iter().flatten() {
+                println!("Deleted Container: {id}");
+            }
+
+            let images = docker
+                .prune_images(None::<PruneImagesOptions<String>>)
+                .await?;
+            for ImageDeleteResponseItem { untagged, deleted } in
+                images.images_deleted.iter().flatten()
+            {
+                if let Some(untagged) = untagged {
+                    println!("Untagged: {untagged}");
+                }
+                if let Some(deleted) = deleted {
+                    println!("Deleted Image: {deleted}");
+                }
+            }
+
+            let networks = docker
+                .prune_networks(None::<PruneNetworksOptions<String>>)
+                .await?;
+            for name in networks.networks_deleted.iter().flatten() {
+                println!("Deleted Network: {name}");
+            }
+
+            println!(
+                "Reclaimed from containers: {}",
+                output.size(containers.space_reclaimed.unwrap_or(0))
+            );
+            println!(
+                "Reclaimed from images: {}",
+                output.size(images.space_reclaimed.unwrap_or(0))
+            );
+            Ok(println!(
+                "Total reclaimed space: {}",
+                output.size(
+                    containers.space_reclaimed.unwrap_or(0) + images.space_reclaimed.unwrap_or(0)
+                )
+            ))
+        }
+        // ./exe system info [--json]
+        SystemOptions::Info(SystemJsonInfo { json }) => {
+            let info = docker.info().await?;
+
+            if *json || output.is_json() {
+                return print_json(output, &info);
+            }
+
+            let SystemInfo {
+                name,
+                server_version,
+                containers,
+                containers_running,
+                containers_paused,
+                containers_stopped,
+                images,
+                driver,
+                kernel_version,
+                operating_system,
+                architecture,
+                ncpu,
+                mem_total,
+                ..
+            } = info;
+
+            let count = |n: Option<i64>| n.unwrap_or(0).to_string();
+
+            // Daemon Info table
+            let mut system_info_table = Table::new();
+            system_info_table.add_row(row![b->"Name", name.unwrap_or_default()]);
+            system_info_table
+                .add_row(row![b->"Server Version", server_version.unwrap_or_default()]);
+            system_info_table.add_row(row![b->"Containers", count(containers)]);
+            system_info_table.add_row(row![b->" Running", count(containers_running)]);
+            system_info_table.add_row(row![b->" Paused", count(containers_paused)]);
+            system_info_table.add_row(row![b->" Stopped", count(containers_stopped)]);
+            system_info_table.add_row(row![b->"Images", count(images)]);
+            system_info_table.add_row(row![b->"Storage Driver", driver.unwrap_or_default()]);
+            system_info_table
+                .add_row(row![b->"Kernel Version", kernel_version.unwrap_or_default()]);
+            system_info_table
+                .add_row(row![b->"Operating System", operating_system.unwrap_or_default()]);
+            system_info_table.add_row(row![b->"Architecture", architecture.unwrap_or_default()]);
+            system_info_table.add_row(row![b->"CPUs", count(ncpu)]);
+            system_info_table.add_row(row![b->"Total Memory", output.size(mem_total.unwrap_or(0))]);
+
+            system_info_table.printstd();
+            Ok(())
+        }
+        // ./exe system version [--json]
+        SystemOptions::Version(SystemJsonInfo { json }) => {
+            let version = docker.version().await?;
+
+            if *json || output.is_json() {
+                return print_json(output, &version);
+            }
+
+            let Version {
+                version,
+                api_version,
+                min_api_version,
+                git_commit,
+                go_version,
+                os,
+                arch,
+                ..
+            } = version;
+
+            // Engine Version table
+            let mut system_version_table = Table::new();
+            system_version_table.add_row(row![b->"Client Version", env!("CARGO_PKG_VERSION")]);
+            system_version_table.add_row(row![b->"Engine Version", version.unwrap_or_default()]);
+            system_version_table.add_row(row![b->"API Version", api_version.unwrap_or_default()]);
+            system_version_table
+                .add_row(row![b->"Min API Version", min_api_version.unwrap_or_default()]);
+            system_version_table.add_row(row![b->"Git Commit", git_commit.unwrap_or_default()]);
+            system_version_table.add_row(row![b->"Go Version", go_version.unwrap_or_default()]);
+            system_version_table.add_row(row![b->"OS/Arch", format!("{}/{}", os.unwrap_or_default(), arch.unwrap_or_default())]);
+
+            system_version_table.printstd();
+            Ok(())
+        }
+    }
+}
+
+/// One line of the `system df` breakdown. Sizes are in bytes.
+struct DiskUsage {
+    kind: &'static str,
+    total: usize,
+    active: usize,
+    size: i64,
+    reclaimable: i64,
+}
+
+/// Summarises the daemon's data usage per resource type the way `docker system df` does:
+/// anything not referenced by a container (or, for build cache, not in use) is reclaimable.
+fn disk_usage(usage: &SystemDataUsageResponse) -> Vec<DiskUsage> {
+    let images = usage.images.as_deref().unwrap_or_default();
+    let images_size = usage
+        .layers_size
+        .unwrap_or_else(|| images.iter().map(|image| image.size).sum());
+    let images_in_use: i64 = images
+        .iter()
+        .filter(|image| image.containers > 0)
+        .map(|image| image.size - image.shared_size.max(0))
+        .sum();
+
+    let containers = usage.containers.as_deref().unwrap_or_default();
+    let running = |state: &Option<String>| state.as_deref() == Some("running");
+
+    let volumes = usage.volumes.as_deref().unwrap_or_default();
+    let volume_usage = |volume: &bollard::models::Volume| {
+        volume
+            .usage_data
+            .as_ref()
+            .map_or((0, 0), |data| (data.size.max(0), data.ref_count))
+    };
+
+    let build_cache = usage.build_cache.as_deref().unwrap_or_default();
+    let in_use = |in_use: Option<bool>| in_use.unwrap_or(false);
+
+    vec![
+        DiskUsage {
+            kind: "Images",
+            total: images.len(),
+            active: images.iter().filter(|image| image.containers > 0).count(),
+            size: images_size,
+            reclaimable: (images_size - images_in_use).max(0),
+        },
+        DiskUsage {
+            kind: "Containers",
+            total: containers.len(),
+            active: containers.iter().filter(|c| running(&c.state)).count(),
+            size: containers.iter().filter_map(|c| c.size_rw).sum(),
+            reclaimable: containers
+                .iter()
+                .filter(|c| !running(&c.state))
+                .filter_map(|c| c.size_rw)
+                .sum(),
+        },
+        DiskUsage {
+            kind: "Local Volumes",
+            total: volumes.len(),
+            active: volumes.iter().filter(|v| volume_usage(v).1 > 0).count(),
+            size: volumes.iter().map(|v| volume_usage(v).0).sum(),
+            reclaimable: volumes
+                .iter()
+                .map(volume_usage)
+                .filter(|(_, ref_count)| *ref_count == 0)
+                .map(|(size, _)| size)
+                .sum(),
+        },
+        DiskUsage {
+            kind: "Build Cache",
+            total: build_cache.len(),
+            active: build_cache.iter().filter(|b| in_use(b.in_use)).count(),
+            size: build_cache.iter().filter_map(|b| b.size).sum(),
+            reclaimable: build_cache
+                .iter()
+                .filter(|b| !in_use(b.in_use) && !b.shared.unwrap_or(false))
+                .filter_map(|b| b.size)
+                .sum(),
+        },
+    ]
+}
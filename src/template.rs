@@ -0,0 +1,139 @@
+//! `--format '{{.State.Status}}'`: pulling fields out of an API response with Go-style dot paths
+//!
+//! Only the field lookups of Go templates are supported, no functions or pipelines. Array
+//! elements can be picked by index, as in `{{.Names.0}}`. Anything beyond that can be written
+//! as a handlebars template with `--format 'tmpl:...'`, which gets the whole handlebars
+//! language: `{{#each}}`, `{{#if}}` and helpers like `{{len}}`.
+
+use handlebars::Handlebars;
+use serde_json::Value;
+
+use crate::error::CliError;
+
+/// Fills every `{{.Path}}` in `template` in from `value`. Strings come out as they are, null
+/// as `<no value>` like Go's templates, and anything else as compact JSON.
+pub fn render(template: &str, value: &Value) -> Result<String, CliError> {
+    let mut rendered = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| CliError::Template(format!("unclosed {{{{ in {template:?}")))?;
+        let action = rest[start + 2..start + end].trim();
+        rendered.push_str(&field(action, value)?);
+        rest = &rest[start + end + 2..];
+    }
+
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Resolves one `.A.B` action against `value`.
+fn field(action: &str, value: &Value) -> Result<String, CliError> {
+    let path = action.strip_prefix('.').ok_or_else(|| {
+        CliError::Template(format!(
+            "{{{{{action}}}}} isn't a field path like {{{{.Id}}}}"
+        ))
+    })?;
+
+    let mut current = value;
+    for key in path.split('.').filter(|key| !key.is_empty()) {
+        let next = match current {
+            Value::Object(fields) => fields.get(key),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        };
+        current = next.ok_or_else(|| CliError::Template(format!("no field {action:?}")))?;
+    }
+
+    Ok(match current {
+        Value::String(text) => text.clone(),
+        Value::Null => "<no value>".to_string(),
+        other => other.to_string(),
+    })
+}
+
+/// Compiles a `tmpl:` template up front so a mistake is reported, with its line and column,
+/// before anything is asked of the daemon, and returns it ready for [`render_handlebars`].
+///
+/// Paths may start with a dot like they do in Go templates (`{{.Names}}`, `{{#each .Ports}}`);
+/// the dot is blanked out rather than removed so error columns still match what was typed.
+pub fn check_handlebars(template: &str) -> Result<String, String> {
+    let mut source = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .map_or(rest.len(), |end| start + end);
+        source.push_str(&rest[..start]);
+
+        let mut previous = '{';
+        for (i, c) in rest[start..end].char_indices() {
+            let next = rest[start + i + c.len_utf8()..].chars().next();
+            let leading_dot = c == '.'
+                && (previous == '{' || previous == '(' || previous.is_whitespace())
+                && next.is_some_and(|next| next.is_alphanumeric() || next == '_');
+            source.push(if leading_dot { ' ' } else { c });
+            previous = c;
+        }
+        rest = &rest[end..];
+    }
+    source.push_str(rest);
+
+    handlebars::Template::compile(&source)
+        .map(|_| source)
+        .map_err(|e| match e.pos() {
+            Some((line, column)) => {
+                format!(
+                    "invalid template at line {line}, column {column}: {}",
+                    e.reason()
+                )
+            }
+            None => format!("invalid template: {}", e.reason()),
+        })
+}
+
+/// Fills a `tmpl:` template in with `value` as its context. Values are printed as they are,
+/// without HTML escaping.
+pub fn render_handlebars(template: &str, value: &Value) -> Result<String, CliError> {
+    let mut registry = Handlebars::new();
+    registry.register_escape_fn(handlebars::no_escape);
+    registry
+        .render_template(template, value)
+        .map_err(|e| CliError::Template(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_handlebars_blanks_leading_dots() {
+        let checked = check_handlebars("{{.Names.[0]}} {{#each .Ports}}{{PublicPort}}{{/each}}");
+        assert_eq!(
+            checked.unwrap(),
+            "{{ Names.[0]}} {{#each  Ports}}{{PublicPort}}{{/each}}"
+        );
+    }
+
+    #[test]
+    fn check_handlebars_keeps_multibyte_text() {
+        let checked = check_handlebars(r#"{{lookup Labels "é"}} café {{.Nom_é}}"#);
+        assert_eq!(checked.unwrap(), r#"{{lookup Labels "é"}} café {{ Nom_é}}"#);
+
+        let value = serde_json::json!({ "Labels": { "é": "ü" }, "Nom_é": "ß" });
+        let rendered = render_handlebars(
+            &check_handlebars(r#"{{lookup Labels "é"}}"#).unwrap(),
+            &value,
+        );
+        assert_eq!(rendered.unwrap(), "ü");
+    }
+
+    #[test]
+    fn check_handlebars_reports_position_of_unclosed_multibyte_tag() {
+        let error = check_handlebars("{{ café").unwrap_err();
+        assert!(error.starts_with("invalid template"), "{error}");
+    }
+}
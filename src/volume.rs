@@ -0,0 +1,126 @@
+//! Working with Docker Volumes
+
+use clap::{Args, Subcommand};
+use std::default::Default;
+
+use bollard::models::Volume as DockerVolume;
+use bollard::volume::{CreateVolumeOptions, ListVolumesOptions, RemoveVolumeOptions};
+use bollard::Docker;
+
+use prettytable::{row, Cell, Row};
+
+use crate::error::{CliError, Failures};
+use crate::{print_json_list, OutputArgs};
+
+#[derive(Debug, Args)]
+pub struct Volume {
+    #[clap(subcommand)]
+    pub command: VolumeOptions,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum VolumeOptions {
+    /// List All Volumes
+    List,
+    /// Create A Volume
+    Create(CreateVolumeInfo),
+    /// Remove One Or More Volumes
+    Rm(RemoveVolumeInfo),
+}
+
+#[derive(Debug, Args)]
+pub struct CreateVolumeInfo {
+    /// Name of the volume, generated by the daemon when omitted
+    pub name: Option<String>,
+
+    /// Volume driver to use
+    #[arg(long, default_value = "local")]
+    pub driver: String,
+}
+
+#[derive(Debug, Args)]
+pub struct RemoveVolumeInfo {
+    /// Names of the Volumes to remove
+    #[arg(required = true)]
+    pub volume_names: Vec<String>,
+
+    /// Remove the volume even if it is in use
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+pub async fn run(
+    docker: &Docker,
+    command: &VolumeOptions,
+    output: &OutputArgs,
+) -> Result<(), CliError> {
+    match command {
+        // ./exe volume list
+        VolumeOptions::List => {
+            let volumes = docker
+                .list_volumes(None::<ListVolumesOptions<String>>)
+                .await?
+                .volumes
+                .unwrap_or_default();
+
+            if output.is_json() {
+                return print_json_list(output, &volumes);
+            }
+
+            // Volume Summary table
+            let mut volume_summary_table =
+                output.table(row![b->"Volume Name", b->"Driver", b->"Mountpoint"]);
+
+            for DockerVolume {
+                name,
+                driver,
+                mountpoint,
+                ..
+            } in volumes.iter()
+            {
+                let volume_summary_row = Row::new(vec![
+                    Cell::new(name),
+                    Cell::new(driver),
+                    Cell::new(mountpoint),
+                ]);
+
+                volume_summary_table.add_row(volume_summary_row);
+            }
+
+            volume_summary_table.printstd();
+            Ok(())
+        }
+        // ./exe volume create [name] [--driver <driver>]
+        VolumeOptions::Create(CreateVolumeInfo { name, driver }) => {
+            let options = CreateVolumeOptions {
+                name: name.clone().unwrap_or_default(),
+                driver: driver.clone(),
+                ..Default::default()
+            };
+
+            let volume = docker.create_volume(options).await?;
+            Ok(println!("Created Volume: {:?}", volume.name))
+        }
+        // ./exe volume rm [-f] <volume_name>...
+        VolumeOptions::Rm(RemoveVolumeInfo {
+            volume_names,
+            force,
+        }) => {
+            let options = RemoveVolumeOptions { force: *force };
+
+            let mut failed = Failures::default();
+            for volume_name in volume_names {
+                match docker.remove_volume(volume_name, Some(options)).await {
+                    Ok(_) => println!("Removed Volume: {volume_name:?}"),
+                    Err(e) => {
+                        eprintln!("Failed to remove volume {volume_name:?}: {e}");
+                        failed.record(e);
+                    }
+                }
+            }
+
+            failed.finish("volume", "be removed")?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! End-to-end checks of the built binary. Those that need a real Docker daemon are ignored, run
+//! them with `cargo test -- --ignored` on a machine that has one
+
+use std::process::{Command, Output};
+
+/// Small and quick to pull, with a `sleep` to keep containers running.
+const IMAGE: &str = "alpine:3.20";
+
+fn locci(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_locci"))
+        .args(args)
+        .output()
+        .expect("locci runs")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// A running container named after the test, removed again when dropped.
+struct Throwaway(String);
+
+impl Throwaway {
+    fn start(name: &str) -> Throwaway {
+        let name = format!("locci-test-{name}-{}", std::process::id());
+        assert!(locci(&["img", "pull", IMAGE]).status.success());
+        let run = locci(&["run", "-d", "--name", &name, IMAGE, "sleep", "300"]);
+        assert!(run.status.success(), "{run:?}");
+        Throwaway(name)
+    }
+
+    fn status(&self) -> String {
+        stdout(&locci(&[
+            "ps",
+            "inspect",
+            &self.0,
+            "-o",
+            "{{.State.Status}}",
+        ]))
+    }
+}
+
+impl Drop for Throwaway {
+    fn drop(&mut self) {
+        let _ = locci(&["ps", "rm", "-f", &self.0]);
+    }
+}
+
+#[test]
+#[ignore = "needs a Docker daemon"]
+fn kill_stops_the_container() {
+    let container = Throwaway::start("kill");
+    assert_eq!(container.status(), "running");
+
+    let kill = locci(&["ps", "kill", &container.0]);
+    assert!(kill.status.success(), "{kill:?}");
+    assert_eq!(container.status(), "exited");
+}
+
+#[test]
+#[ignore = "needs a Docker daemon"]
+fn kill_of_missing_container_fails() {
+    let kill = locci(&["ps", "kill", "locci-test-no-such-container"]);
+    assert_eq!(kill.status.code(), Some(4), "{kill:?}");
+}
+
+#[test]
+fn missing_subcommand_is_a_usage_error() {
+    assert_eq!(locci(&[]).status.code(), Some(2));
+}
+
+#[test]
+fn unreachable_daemon_exits_3() {
+    let ps = locci(&["--socket", "/nonexistent/docker.sock", "ps", "info"]);
+    assert_eq!(ps.status.code(), Some(3), "{ps:?}");
+}